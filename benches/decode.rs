@@ -0,0 +1,98 @@
+//! Decode the example corpus, reporting time and memory.
+//!
+//! Run with `cargo bench`. Memory is measured with a counting allocator:
+//! `allocated` is the total allocated while decoding, and `retained` is how
+//! much of that the decoded containers hold on to.
+//!
+//! As a baseline, the same strings are also decoded into untyped
+//! `serde_json::Value`s, which keep every name as a separate `String`.
+
+use factorio_blueprint::BlueprintCodec;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::io::Read;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        LIVE.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+const ROUNDS: u32 = 10;
+
+/// decode the json payload of the string without the typed model
+fn decode_value(string: &str) -> serde_json::Value {
+    let mut json = String::new();
+    BlueprintCodec::decode_reader(string.trim().as_bytes(), |mut reader| {
+        reader.read_to_string(&mut json).map(|_| ())
+    })
+    .unwrap();
+    serde_json::from_str(&json).unwrap()
+}
+
+/// report the memory and time which `decode` takes over the corpus
+fn measure<T>(name: &str, strings: &[String], decode: impl Fn(&str) -> T) {
+    // warm up
+    for string in strings {
+        decode(string);
+    }
+
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let allocated = ALLOCATED.load(Ordering::Relaxed);
+    let live = LIVE.load(Ordering::Relaxed);
+    let decoded = strings
+        .iter()
+        .map(|string| decode(string))
+        .collect::<Vec<_>>();
+    println!(
+        "{}: {} allocations, {} bytes allocated, {} bytes retained",
+        name,
+        ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+        ALLOCATED.load(Ordering::Relaxed) - allocated,
+        LIVE.load(Ordering::Relaxed) - live,
+    );
+    drop(decoded);
+
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        for string in strings {
+            decode(string);
+        }
+    }
+    println!("{}: {:?} per round", name, start.elapsed() / ROUNDS);
+}
+
+fn main() {
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/examples");
+    let strings = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| std::fs::read_to_string(entry.unwrap().path()).unwrap())
+        .collect::<Vec<_>>();
+
+    measure("decode corpus", &strings, |string| {
+        BlueprintCodec::decode_string(string).unwrap()
+    });
+    measure(
+        "baseline: decode to serde_json::Value",
+        &strings,
+        decode_value,
+    );
+}
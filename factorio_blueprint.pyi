@@ -0,0 +1,38 @@
+"""Reading and writing Factorio blueprint strings.
+
+Containers are dicts with exactly the shape of the blueprint's json, such as
+``{"blueprint": {"item": "blueprint", "entities": [...], ...}}``.
+"""
+
+from typing import Any, Dict
+
+Container = Dict[str, Any]
+
+class BlueprintCodec:
+    """Reads and writes blueprint strings."""
+
+    @staticmethod
+    def decode(blueprint: str) -> Container:
+        """decode a blueprint string into a dict"""
+    @staticmethod
+    def encode(container: Container) -> str:
+        """encode a dict, of the shape returned by `decode`, as a blueprint
+        string"""
+
+class BlueprintError(Exception):
+    """Raised when a blueprint cannot be decoded or encoded."""
+
+class InputError(BlueprintError):
+    """Raised when the input cannot be read, or is too large."""
+
+class VersionError(BlueprintError):
+    """Raised when a blueprint string has an unknown version byte."""
+
+class Base64Error(BlueprintError):
+    """Raised when a blueprint string is not valid base64."""
+
+class ZlibError(BlueprintError):
+    """Raised when a blueprint string does not hold a valid zlib stream."""
+
+class JsonError(BlueprintError):
+    """Raised when a blueprint's json does not describe a valid container."""
@@ -0,0 +1,319 @@
+/* Generated by cbindgen from src/ffi.rs; do not edit.
+ *
+ * Ownership: an FbContainer returned through an out parameter is owned by
+ * the caller and released with fb_container_free; a char * returned through
+ * an out parameter is owned by the caller and released with fb_string_free.
+ * Every other pointer, including those in an FbStr, borrows from the
+ * container it was reached through and is valid until that container is
+ * freed. FbStrs are not nul-terminated.
+ *
+ * Errors: fallible functions return FB_STATUS_OK on success; otherwise
+ * fb_last_error describes the error, and out parameters are untouched.
+ */
+
+#ifndef FACTORIO_BLUEPRINT_H
+#define FACTORIO_BLUEPRINT_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+// The outcome of a fallible function.
+typedef enum FbStatus {
+  FB_STATUS_OK = 0,
+  // A pointer argument was null.
+  FB_STATUS_NULL_POINTER = 1,
+  // The input could not be read, or was too large.
+  FB_STATUS_INPUT = 2,
+  // The blueprint string had an unknown version byte.
+  FB_STATUS_VERSION = 3,
+  // The blueprint string was not valid base64.
+  FB_STATUS_BASE64 = 4,
+  // The blueprint string did not hold a valid zlib stream.
+  FB_STATUS_ZLIB = 5,
+  // The json did not describe a valid container.
+  FB_STATUS_JSON = 6,
+  // Any other error.
+  FB_STATUS_OTHER = 7,
+  // The library panicked; this is a bug.
+  FB_STATUS_PANIC = 8,
+} FbStatus;
+
+// The kind of thing an `FbContainer` holds.
+typedef enum FbContainerKind {
+  FB_CONTAINER_KIND_BLUEPRINT_BOOK = 0,
+  FB_CONTAINER_KIND_BLUEPRINT = 1,
+  FB_CONTAINER_KIND_DECONSTRUCTION_PLANNER = 2,
+  FB_CONTAINER_KIND_UPGRADE_PLANNER = 3,
+} FbContainerKind;
+
+// https://wiki.factorio.com/Blueprint_string_format#Blueprint_object
+typedef struct FbBlueprint FbBlueprint;
+
+// https://wiki.factorio.com/Blueprint_string_format#Blueprint_book_object
+typedef struct FbBlueprintBook FbBlueprintBook;
+
+// `Container`s are the primary entry point for this library: they contain
+// either a single blueprint, or a blueprint book.
+typedef struct FbContainer FbContainer;
+
+// https://wiki.factorio.com/Blueprint_string_format#Entity_object
+//
+// Settings which only a few kinds of entity have are kept in
+// [`EntityExtra`], which is only allocated for entities which have any.
+// This keeps the entities of large blueprints small in memory; it does not
+// affect their json, nor their equality: an entity whose `extra` is
+// allocated but empty equals one whose `extra` is `None`.
+typedef struct FbEntity FbEntity;
+
+// https://wiki.factorio.com/Blueprint_string_format#Tile_object
+typedef struct FbTile FbTile;
+
+// A borrowed utf8 string, which is not nul-terminated. `ptr` is null when
+// the string is absent.
+typedef struct FbStr {
+  const char *ptr;
+  size_t len;
+} FbStr;
+
+// A position, in tiles.
+typedef struct FbPosition {
+  double x;
+  double y;
+} FbPosition;
+
+
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// a description of the most recent error on this thread, or null if there
+// has been none
+//
+// The string is owned by the library, and valid until the next fallible
+// call on this thread.
+const char *fb_last_error(void);
+
+// decode the `len` bytes of the blueprint string at `data` into a new
+// container
+//
+// # Safety
+//
+// `data` must point to `len` readable bytes, and `out` to a writable
+// pointer.
+enum FbStatus fb_decode(const char *data, size_t len, struct FbContainer **out);
+
+// read the `len` bytes of json at `data` into a new container
+//
+// # Safety
+//
+// `data` must point to `len` readable bytes, and `out` to a writable
+// pointer.
+enum FbStatus fb_container_from_json(const char *data, size_t len, struct FbContainer **out);
+
+// release a container; null is ignored
+//
+// # Safety
+//
+// `container` must be null or have been returned by this library, and not
+// already freed.
+void fb_container_free(struct FbContainer *container);
+
+// encode the container as a blueprint string, to be released with
+// `fb_string_free`
+//
+// # Safety
+//
+// `container` must be a valid container, and `out` must point to a
+// writable pointer.
+enum FbStatus fb_encode(const struct FbContainer *container, char **out);
+
+// serialize the container as json, to be released with `fb_string_free`
+//
+// # Safety
+//
+// `container` must be a valid container, and `out` must point to a
+// writable pointer.
+enum FbStatus fb_container_to_json(const struct FbContainer *container, char **out);
+
+// serialize the entity as json, to be released with `fb_string_free`
+//
+// # Safety
+//
+// `entity` must be a valid entity, and `out` must point to a writable
+// pointer.
+enum FbStatus fb_entity_to_json(const struct FbEntity *entity, char **out);
+
+// release a string returned by this library; null is ignored
+//
+// # Safety
+//
+// `s` must be null or have been returned through an out parameter of this
+// library, and not already freed.
+void fb_string_free(char *s);
+
+// what the container holds
+//
+// # Safety
+//
+// `container` must be a valid container.
+enum FbContainerKind fb_container_kind(const struct FbContainer *container);
+
+// the label of whatever the container holds
+//
+// # Safety
+//
+// `container` must be a valid container.
+struct FbStr fb_container_label(const struct FbContainer *container);
+
+// the game version of whatever the container holds
+//
+// # Safety
+//
+// `container` must be a valid container.
+uint64_t fb_container_version(const struct FbContainer *container);
+
+// the book which the container holds, or null if it holds something else
+//
+// # Safety
+//
+// `container` must be a valid container.
+const struct FbBlueprintBook *fb_container_book(const struct FbContainer *container);
+
+// the blueprint which the container holds, or null if it holds something
+// else
+//
+// # Safety
+//
+// `container` must be a valid container.
+const struct FbBlueprint *fb_container_blueprint(const struct FbContainer *container);
+
+// the number of pages in the book
+//
+// # Safety
+//
+// `book` must be a valid book.
+size_t fb_book_page_count(const struct FbBlueprintBook *book);
+
+// the page at position `n` of the book, or null if there are not that
+// many pages
+//
+// Pages are in the order in which the book stores them, which need not be
+// the order of their indices; see `fb_book_page_index`.
+//
+// # Safety
+//
+// `book` must be a valid book.
+const struct FbContainer *fb_book_page(const struct FbBlueprintBook *book, size_t n);
+
+// the index of the page at position `n` of the book, or `SIZE_MAX` if
+// there are not that many pages
+//
+// This is the slot which the page occupies in the game, which is not
+// necessarily its position.
+//
+// # Safety
+//
+// `book` must be a valid book.
+size_t fb_book_page_index(const struct FbBlueprintBook *book, size_t n);
+
+// the index of the page which is selected in the book
+//
+// Compare it with `fb_book_page_index`, not with positions.
+//
+// # Safety
+//
+// `book` must be a valid book.
+size_t fb_book_active_index(const struct FbBlueprintBook *book);
+
+// the description of the blueprint
+//
+// # Safety
+//
+// `blueprint` must be a valid blueprint.
+struct FbStr fb_blueprint_description(const struct FbBlueprint *blueprint);
+
+// the number of entities in the blueprint
+//
+// # Safety
+//
+// `blueprint` must be a valid blueprint.
+size_t fb_blueprint_entity_count(const struct FbBlueprint *blueprint);
+
+// the entity at position `n` of the blueprint, or null if there are not
+// that many entities
+//
+// # Safety
+//
+// `blueprint` must be a valid blueprint.
+const struct FbEntity *fb_blueprint_entity(const struct FbBlueprint *blueprint, size_t n);
+
+// the number of tiles in the blueprint
+//
+// # Safety
+//
+// `blueprint` must be a valid blueprint.
+size_t fb_blueprint_tile_count(const struct FbBlueprint *blueprint);
+
+// the tile at position `n` of the blueprint, or null if there are not that
+// many tiles
+//
+// # Safety
+//
+// `blueprint` must be a valid blueprint.
+const struct FbTile *fb_blueprint_tile(const struct FbBlueprint *blueprint, size_t n);
+
+// the number by which wires and schedules refer to the entity
+//
+// # Safety
+//
+// `entity` must be a valid entity.
+size_t fb_entity_number(const struct FbEntity *entity);
+
+// the prototype name of the entity, such as `transport-belt`
+//
+// # Safety
+//
+// `entity` must be a valid entity.
+struct FbStr fb_entity_name(const struct FbEntity *entity);
+
+// the position of the entity's center
+//
+// # Safety
+//
+// `entity` must be a valid entity.
+struct FbPosition fb_entity_position(const struct FbEntity *entity);
+
+// the direction of the entity, in eighths of a turn clockwise from north
+//
+// # Safety
+//
+// `entity` must be a valid entity.
+uint8_t fb_entity_direction(const struct FbEntity *entity);
+
+// the recipe of the entity, if it is a machine with one set
+//
+// # Safety
+//
+// `entity` must be a valid entity.
+struct FbStr fb_entity_recipe(const struct FbEntity *entity);
+
+// the prototype name of the tile, such as `stone-path`
+//
+// # Safety
+//
+// `tile` must be a valid tile.
+struct FbStr fb_tile_name(const struct FbTile *tile);
+
+// the position of the tile's top left corner
+//
+// # Safety
+//
+// `tile` must be a valid tile.
+struct FbPosition fb_tile_position(const struct FbTile *tile);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* FACTORIO_BLUEPRINT_H */
@@ -0,0 +1,341 @@
+//! Asynchronous counterparts to the blocking blueprint string pipeline.
+//!
+//! The stages are the same as for [`BlueprintCodec::decode_reader`] and
+//! [`BlueprintCodec::encode_writer`]: whitespace removal, the version prefix,
+//! base64, and zlib. `serde_json` has no async interface, so the json payload
+//! itself is buffered in memory before it is (de)serialized.
+
+use crate::{
+    encode_options::EncodeOptions,
+    limits::{self, DecodeLimits, LimitedReader},
+    line_wrapper::LineWrapper,
+    stage,
+    version_prefix::{VersionPrefixReader, VersionPrefixWriter},
+    whitespace_remover::WhitespaceRemover,
+    BlueprintCodec, Container, Error, Result,
+};
+use async_compression::{
+    tokio::{bufread::ZlibDecoder, write::ZlibEncoder},
+    Level,
+};
+use std::io::{Error as IoError, ErrorKind};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader, ReadBuf};
+
+/// Size of the chunks of base64 data processed at a time. Must be a multiple of 4.
+const BASE64_CHUNK_SIZE: usize = 1024;
+/// Size of the chunks of binary data encoded at a time. Must be a multiple of 3.
+const BINARY_CHUNK_SIZE: usize = BASE64_CHUNK_SIZE / 4 * 3;
+
+/// Async reader adaptor which decodes standard base64 from an inner reader.
+pub struct Base64Decoder<R> {
+    inner: R,
+    /// base64 data which has been read but not yet decoded
+    encoded: Vec<u8>,
+    /// number of base64 bytes decoded so far; used to report accurate offsets
+    consumed: usize,
+    decoded: Vec<u8>,
+    decoded_offset: usize,
+    eof: bool,
+}
+
+impl<R> Base64Decoder<R> {
+    pub fn new(inner: R) -> Base64Decoder<R> {
+        Base64Decoder {
+            inner,
+            encoded: Vec::with_capacity(BASE64_CHUNK_SIZE),
+            consumed: 0,
+            decoded: Vec::with_capacity(BINARY_CHUNK_SIZE),
+            decoded_offset: 0,
+            eof: false,
+        }
+    }
+
+    /// decode the first `len` bytes of the pending base64 data
+    fn decode(&mut self, len: usize) -> std::io::Result<()> {
+        use base64::DecodeError;
+
+        self.decoded.clear();
+        self.decoded_offset = 0;
+        base64::decode_config_buf(&self.encoded[..len], base64::STANDARD, &mut self.decoded)
+            .map_err(|err| {
+                let err = match err {
+                    DecodeError::InvalidByte(offset, byte) => {
+                        DecodeError::InvalidByte(self.consumed + offset, byte)
+                    }
+                    DecodeError::InvalidLastSymbol(offset, byte) => {
+                        DecodeError::InvalidLastSymbol(self.consumed + offset, byte)
+                    }
+                    DecodeError::InvalidLength => DecodeError::InvalidLength,
+                };
+                IoError::new(ErrorKind::InvalidData, err)
+            })?;
+        self.encoded.drain(..len);
+        self.consumed += len;
+        Ok(())
+    }
+}
+
+impl<R> AsyncRead for Base64Decoder<R>
+where
+    R: AsyncRead + Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            if this.decoded_offset < this.decoded.len() {
+                let available = &this.decoded[this.decoded_offset..];
+                let n = available.len().min(buf.remaining());
+                buf.put_slice(&available[..n]);
+                this.decoded_offset += n;
+                return Poll::Ready(Ok(()));
+            }
+
+            if this.eof {
+                if this.encoded.is_empty() {
+                    return Poll::Ready(Ok(()));
+                }
+                // whatever is left is the final, possibly unpadded, quantum
+                this.decode(this.encoded.len())?;
+                continue;
+            }
+
+            let mut chunk = [0; BASE64_CHUNK_SIZE];
+            let mut chunk_buf = ReadBuf::new(&mut chunk);
+            match Pin::new(&mut this.inner).poll_read(cx, &mut chunk_buf) {
+                Poll::Ready(Ok(())) => {}
+                other => return other,
+            }
+            if chunk_buf.filled().is_empty() {
+                this.eof = true;
+                continue;
+            }
+            this.encoded.extend_from_slice(chunk_buf.filled());
+
+            // only decode whole quanta until we know where the stream ends
+            let whole = this.encoded.len() / 4 * 4;
+            if whole > 0 {
+                this.decode(whole)?;
+            }
+        }
+    }
+}
+
+/// Async writer adaptor which encodes data written to it as standard base64.
+///
+/// Padding is written when the writer is shut down.
+pub struct Base64Encoder<W> {
+    inner: W,
+    /// binary data which has been accepted but not yet encoded
+    pending: Vec<u8>,
+    encoded: String,
+    encoded_offset: usize,
+}
+
+impl<W> Base64Encoder<W> {
+    pub fn new(inner: W) -> Base64Encoder<W> {
+        Base64Encoder {
+            inner,
+            pending: Vec::with_capacity(BINARY_CHUNK_SIZE + 2),
+            encoded: String::with_capacity(BASE64_CHUNK_SIZE + 4),
+            encoded_offset: 0,
+        }
+    }
+}
+
+impl<W> Base64Encoder<W>
+where
+    W: AsyncWrite + Unpin,
+{
+    /// write all encoded data to the inner writer
+    fn poll_drain(&mut self, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        while self.encoded_offset < self.encoded.len() {
+            match Pin::new(&mut self.inner)
+                .poll_write(cx, &self.encoded.as_bytes()[self.encoded_offset..])
+            {
+                Poll::Ready(Ok(0)) => return Poll::Ready(Err(ErrorKind::WriteZero.into())),
+                Poll::Ready(Ok(n)) => self.encoded_offset += n,
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        self.encoded.clear();
+        self.encoded_offset = 0;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<W> AsyncWrite for Base64Encoder<W>
+where
+    W: AsyncWrite + Unpin,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = self.get_mut();
+        match this.poll_drain(cx) {
+            Poll::Ready(Ok(())) => {}
+            Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+            Poll::Pending => return Poll::Pending,
+        }
+
+        let n = buf.len().min(BINARY_CHUNK_SIZE);
+        this.pending.extend_from_slice(&buf[..n]);
+        let whole = this.pending.len() / 3 * 3;
+        base64::encode_config_buf(&this.pending[..whole], base64::STANDARD, &mut this.encoded);
+        this.pending.drain(..whole);
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        match this.poll_drain(cx) {
+            Poll::Ready(Ok(())) => Pin::new(&mut this.inner).poll_flush(cx),
+            other => other,
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        if !this.pending.is_empty() {
+            base64::encode_config_buf(&this.pending, base64::STANDARD, &mut this.encoded);
+            this.pending.clear();
+        }
+        match this.poll_drain(cx) {
+            Poll::Ready(Ok(())) => Pin::new(&mut this.inner).poll_shutdown(cx),
+            other => other,
+        }
+    }
+}
+
+/// Flushes instead of shutting down the inner writer.
+///
+/// Like the blocking `encode`, `encode_async` should not close the
+/// caller's writer.
+struct KeepOpen<W>(W);
+
+impl<W> AsyncWrite for KeepOpen<W>
+where
+    W: AsyncWrite + Unpin,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.get_mut().0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.get_mut().0).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.get_mut().0).poll_flush(cx)
+    }
+}
+
+impl BlueprintCodec {
+    /// write the blueprint string to the given async writer
+    ///
+    /// The writer is flushed, but not shut down.
+    pub async fn encode_async<W>(writer: W, container: &Container) -> Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        Self::encode_async_with(writer, container, &EncodeOptions::default()).await
+    }
+
+    /// write the blueprint string to the given async writer, according to
+    /// the given options
+    ///
+    /// The writer is flushed, but not shut down.
+    pub async fn encode_async_with<W>(
+        writer: W,
+        container: &Container,
+        options: &EncodeOptions,
+    ) -> Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        options.validate()?;
+        let json = if options.canonical {
+            let mut container = container.clone();
+            container.canonicalize();
+            serde_json::to_vec(&container)?
+        } else {
+            serde_json::to_vec(container)?
+        };
+
+        let writer = LineWrapper::new(options.line_width, KeepOpen(writer));
+        let writer = VersionPrefixWriter::new(options.version, writer);
+        let writer = Base64Encoder::new(writer);
+        let level = Level::Precise(options.compression_level as i32);
+        let mut writer = ZlibEncoder::with_quality(writer, level);
+        writer.write_all(&json).await?;
+        // shutting down finishes the zlib stream and writes the base64 padding
+        writer.shutdown().await?;
+        Ok(())
+    }
+
+    /// produce a new owned string containing the blueprint string
+    pub async fn encode_string_async(container: &Container) -> Result<String> {
+        let mut out = Vec::new();
+        Self::encode_async(&mut out, container).await?;
+        String::from_utf8(out).map_err(|e| e.into())
+    }
+
+    /// read the blueprint string from the given async reader
+    ///
+    /// This applies no limits: use `decode_async_with_limits` for untrusted
+    /// input.
+    pub async fn decode_async<R>(reader: R) -> Result<Container>
+    where
+        R: AsyncRead + Unpin,
+    {
+        Self::decode_async_with_limits(reader, &DecodeLimits::unlimited()).await
+    }
+
+    /// read the blueprint string from the given async reader, failing if it
+    /// exceeds any of the given limits
+    pub async fn decode_async_with_limits<R>(reader: R, limits: &DecodeLimits) -> Result<Container>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let reader = LimitedReader::new(reader, limits.max_input_bytes, |limit| {
+            Error::InputTooLarge { limit }
+        });
+        let reader = WhitespaceRemover::new(reader);
+        let mut reader = VersionPrefixReader::new('0', reader);
+        let mut json = Vec::new();
+        let result = {
+            let reader = Base64Decoder::new(&mut reader);
+            let mut reader = ZlibDecoder::new(BufReader::new(reader));
+            let result = LimitedReader::new(&mut reader, limits.max_decompressed_bytes, |limit| {
+                Error::DecompressedTooLarge { limit }
+            })
+            .read_to_end(&mut json)
+            .await;
+            result.map_err(|err| {
+                if limits::is_exceeded(&err) {
+                    return err;
+                }
+                // four base64 characters encode three bytes
+                let compressed = reader.get_ref().get_ref().consumed / 4 * 3;
+                stage::tag_decompression(compressed as u64, err)
+            })
+        };
+
+        BlueprintCodec::check_decoding(result, &reader).map_err(limits::unwrap_exceeded)?;
+        let container = BlueprintCodec::deserialize_json(json.as_slice())?;
+        limits.check(&container)?;
+        Ok(container)
+    }
+}
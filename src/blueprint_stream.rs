@@ -0,0 +1,159 @@
+//! Streaming encoding of very large blueprints.
+//!
+//! [`BlueprintCodec::encode`](crate::BlueprintCodec::encode) needs the whole
+//! [`Blueprint`] in memory. A [`BlueprintStream`] instead writes the
+//! blueprint's header up front, then each entity, tile, and schedule as it is
+//! handed over, straight into the compressor: memory use does not grow with
+//! the size of the blueprint.
+//!
+//! ```
+//! # use factorio_blueprint::{objects::{Blueprint, Position, Tile}, BlueprintCodec};
+//! # use noisy_float::types::r64;
+//! let mut out = Vec::new();
+//! BlueprintCodec::encode_blueprint_stream(&mut out, &Blueprint::default(), |stream| {
+//!     stream.tiles((0..1000).map(|x| Tile {
+//!         name: "refined-concrete".into(),
+//!         position: Position { x: r64(x as f64), y: r64(0.0) },
+//!     }))
+//! })
+//! .unwrap();
+//! ```
+
+use crate::{
+    objects::{Blueprint, Entity, Schedule, Tile},
+    Error, Result,
+};
+use serde::Serialize;
+use std::io::Write;
+
+/// The arrays of a blueprint, in the order in which they must be written.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+enum Section {
+    Header,
+    Entities,
+    Tiles,
+    Schedules,
+}
+
+impl Section {
+    fn key(self) -> &'static str {
+        match self {
+            Section::Header => unreachable!("the header is not an array"),
+            Section::Entities => "entities",
+            Section::Tiles => "tiles",
+            Section::Schedules => "schedules",
+        }
+    }
+}
+
+/// Writer of the json of a single blueprint, whose entities, tiles, and
+/// schedules are supplied one at a time.
+///
+/// All entities must be written before any tiles, and all tiles before any
+/// schedules; writing them out of order is an error.
+pub struct BlueprintStream<W: Write> {
+    writer: W,
+    section: Section,
+    /// whether anything has been written to the current section
+    empty: bool,
+}
+
+impl<W: Write> BlueprintStream<W> {
+    /// write the header of the blueprint, and any entities, tiles, and
+    /// schedules it already contains
+    pub(crate) fn new(mut writer: W, header: &Blueprint) -> Result<BlueprintStream<W>> {
+        let mut fields = match serde_json::to_value(header)? {
+            serde_json::Value::Object(fields) => fields,
+            _ => unreachable!("blueprints serialize to objects"),
+        };
+        let arrays = [Section::Entities, Section::Tiles, Section::Schedules]
+            .iter()
+            .map(|section| (*section, fields.remove(section.key())))
+            .collect::<Vec<_>>();
+
+        writer.write_all(br#"{"blueprint":{"#)?;
+        for (n, (key, value)) in fields.iter().enumerate() {
+            if n > 0 {
+                writer.write_all(b",")?;
+            }
+            serde_json::to_writer(&mut writer, key)?;
+            writer.write_all(b":")?;
+            serde_json::to_writer(&mut writer, value)?;
+        }
+
+        let mut stream = BlueprintStream {
+            writer,
+            section: Section::Header,
+            empty: true,
+        };
+        for (section, values) in arrays {
+            if let Some(serde_json::Value::Array(values)) = values {
+                for value in values {
+                    stream.write(section, &value)?;
+                }
+            }
+        }
+        Ok(stream)
+    }
+
+    fn write<T: Serialize>(&mut self, section: Section, value: &T) -> Result<()> {
+        if section < self.section {
+            return Err(Error::StreamOrder {
+                found: section.key(),
+                after: self.section.key(),
+            });
+        }
+        if section > self.section {
+            self.close_section()?;
+            self.section = section;
+            write!(self.writer, r#","{}":["#, section.key())?;
+        } else if !self.empty {
+            self.writer.write_all(b",")?;
+        }
+        self.empty = false;
+        serde_json::to_writer(&mut self.writer, value)?;
+        Ok(())
+    }
+
+    fn close_section(&mut self) -> Result<()> {
+        if self.section != Section::Header {
+            self.writer.write_all(b"]")?;
+        }
+        Ok(())
+    }
+
+    pub fn entity(&mut self, entity: &Entity) -> Result<()> {
+        self.write(Section::Entities, entity)
+    }
+
+    pub fn tile(&mut self, tile: &Tile) -> Result<()> {
+        self.write(Section::Tiles, tile)
+    }
+
+    pub fn schedule(&mut self, schedule: &Schedule) -> Result<()> {
+        self.write(Section::Schedules, schedule)
+    }
+
+    pub fn entities<I: IntoIterator<Item = Entity>>(&mut self, entities: I) -> Result<()> {
+        entities
+            .into_iter()
+            .try_for_each(|entity| self.entity(&entity))
+    }
+
+    pub fn tiles<I: IntoIterator<Item = Tile>>(&mut self, tiles: I) -> Result<()> {
+        tiles.into_iter().try_for_each(|tile| self.tile(&tile))
+    }
+
+    pub fn schedules<I: IntoIterator<Item = Schedule>>(&mut self, schedules: I) -> Result<()> {
+        schedules
+            .into_iter()
+            .try_for_each(|schedule| self.schedule(&schedule))
+    }
+
+    /// close the json of the blueprint
+    pub(crate) fn finish(mut self) -> Result<W> {
+        self.close_section()?;
+        self.writer.write_all(b"}}")?;
+        Ok(self.writer)
+    }
+}
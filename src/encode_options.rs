@@ -0,0 +1,83 @@
+//! Options controlling how blueprint strings are encoded.
+
+use crate::{Error, Result};
+
+/// The highest zlib compression level.
+pub const MAX_COMPRESSION_LEVEL: u32 = 9;
+
+/// Options controlling how blueprint strings are encoded.
+///
+/// The default produces the same strings as Factorio itself.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct EncodeOptions {
+    /// zlib compression level, from 0 (store only) to 9 (best compression).
+    /// Encoding fails with [`Error::CompressionLevel`] for any other level.
+    pub compression_level: u32,
+    /// Version byte which prefixes the string. Factorio only understands `'0'`.
+    /// Encoding fails with [`Error::VersionByte`] unless it is ascii.
+    pub version: char,
+    /// If set, insert a newline after every this many characters of output,
+    /// which keeps strings intact in emails and wikis. Decoding ignores
+    /// whitespace, so wrapped strings decode normally.
+    pub line_width: Option<usize>,
+    /// Canonicalize containers before encoding them, so that containers
+    /// which differ only in the order of their entities, tiles, icons, or
+    /// book pages encode to identical strings.
+    ///
+    /// Encoding is always deterministic: equal containers encode to
+    /// identical strings whether or not this is set.
+    pub canonical: bool,
+}
+
+impl Default for EncodeOptions {
+    fn default() -> EncodeOptions {
+        EncodeOptions {
+            compression_level: 9,
+            version: '0',
+            line_width: None,
+            canonical: false,
+        }
+    }
+}
+
+impl EncodeOptions {
+    /// Fastest compression: for bulk re-encoding where string size matters
+    /// less than throughput.
+    pub fn fast() -> EncodeOptions {
+        EncodeOptions {
+            compression_level: 1,
+            ..EncodeOptions::default()
+        }
+    }
+
+    /// zlib's default compression level: most of the size benefit of the
+    /// best compression, in much less time.
+    pub fn balanced() -> EncodeOptions {
+        EncodeOptions {
+            compression_level: 6,
+            ..EncodeOptions::default()
+        }
+    }
+
+    /// Canonical encoding with the best compression: suitable for content
+    /// addressing and for diffs.
+    pub fn canonical() -> EncodeOptions {
+        EncodeOptions {
+            canonical: true,
+            ..EncodeOptions::default()
+        }
+    }
+
+    /// check that these options can be encoded with
+    pub fn validate(&self) -> Result<()> {
+        if self.compression_level > MAX_COMPRESSION_LEVEL {
+            return Err(Error::CompressionLevel {
+                level: self.compression_level,
+            });
+        }
+        if !self.version.is_ascii() {
+            return Err(Error::VersionByte(self.version));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,559 @@
+//! A C ABI for the codec and the object model.
+//!
+//! Available with the `ffi` feature, which builds the crate's `cdylib` with
+//! these functions exported; `include/factorio_blueprint.h` declares them.
+//!
+//! A blueprint string is decoded with `fb_decode` into an `FbContainer`
+//! handle, which is walked through accessor functions and encoded again
+//! with `fb_encode`. Anything which the accessors do not reach is available
+//! as json, from `fb_container_to_json` and `fb_entity_to_json`.
+//!
+//! # Ownership
+//!
+//! - An `FbContainer` returned through an out parameter is owned by the
+//!   caller, and must be released with `fb_container_free`.
+//! - A `char *` returned through an out parameter is a nul-terminated string
+//!   owned by the caller, and must be released with `fb_string_free`.
+//! - Every other pointer, including those in an `FbStr`, borrows from the
+//!   container it was reached through, and is valid until that container is
+//!   freed. `FbStr`s are not nul-terminated.
+//! - Input pointers are only read during the call.
+//!
+//! # Errors
+//!
+//! Fallible functions return an `FbStatus`, which is `FB_STATUS_OK` on
+//! success. Otherwise it classifies the error, and `fb_last_error` describes
+//! it; out parameters are then left untouched. Accessors cannot fail; they
+//! must be passed valid, non-null handles.
+
+use crate::{
+    objects::{Blueprint, BlueprintBook, Direction, Entity, Position, Tile},
+    BlueprintCodec, Container, Error, Result, Stage,
+};
+use std::cell::RefCell;
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+/// The outcome of a fallible function.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FbStatus {
+    Ok = 0,
+    /// A pointer argument was null.
+    NullPointer = 1,
+    /// The input could not be read, or was too large.
+    Input = 2,
+    /// The blueprint string had an unknown version byte.
+    Version = 3,
+    /// The blueprint string was not valid base64.
+    Base64 = 4,
+    /// The blueprint string did not hold a valid zlib stream.
+    Zlib = 5,
+    /// The json did not describe a valid container.
+    Json = 6,
+    /// Any other error.
+    Other = 7,
+    /// The library panicked; this is a bug.
+    Panic = 8,
+}
+
+impl From<&Error> for FbStatus {
+    fn from(err: &Error) -> FbStatus {
+        match err.stage() {
+            Some(Stage::Input) => FbStatus::Input,
+            Some(Stage::Version) => FbStatus::Version,
+            Some(Stage::Base64) => FbStatus::Base64,
+            Some(Stage::Zlib) => FbStatus::Zlib,
+            Some(Stage::Json) => FbStatus::Json,
+            None => FbStatus::Other,
+        }
+    }
+}
+
+/// The kind of thing an `FbContainer` holds.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FbContainerKind {
+    BlueprintBook = 0,
+    Blueprint = 1,
+    DeconstructionPlanner = 2,
+    UpgradePlanner = 3,
+}
+
+/// A borrowed utf8 string, which is not nul-terminated. `ptr` is null when
+/// the string is absent.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct FbStr {
+    pub ptr: *const c_char,
+    pub len: usize,
+}
+
+impl FbStr {
+    fn new(s: &str) -> FbStr {
+        FbStr {
+            ptr: s.as_ptr().cast(),
+            len: s.len(),
+        }
+    }
+
+    fn optional(s: Option<&str>) -> FbStr {
+        s.map_or(
+            FbStr {
+                ptr: ptr::null(),
+                len: 0,
+            },
+            FbStr::new,
+        )
+    }
+}
+
+/// A position, in tiles.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct FbPosition {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl From<&Position> for FbPosition {
+    fn from(position: &Position) -> FbPosition {
+        FbPosition {
+            x: position.x.raw(),
+            y: position.y.raw(),
+        }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    let message = CString::new(message.replace('\0', "\\0")).expect("nuls were replaced");
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// run a fallible function body, recording its error if it fails
+fn run(body: impl FnOnce() -> Result<()>) -> FbStatus {
+    match catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(())) => FbStatus::Ok,
+        Ok(Err(err)) => {
+            let status = FbStatus::from(&err);
+            set_last_error(err.to_string());
+            status
+        }
+        Err(_) => {
+            set_last_error("factorio-blueprint panicked".into());
+            FbStatus::Panic
+        }
+    }
+}
+
+fn null_pointer() -> FbStatus {
+    set_last_error("a pointer argument was null".into());
+    FbStatus::NullPointer
+}
+
+fn into_c_string(s: String) -> *mut c_char {
+    // neither blueprint strings nor json contain nul bytes
+    CString::new(s)
+        .expect("output contains no nul bytes")
+        .into_raw()
+}
+
+/// a description of the most recent error on this thread, or null if there
+/// has been none
+///
+/// The string is owned by the library, and valid until the next fallible
+/// call on this thread.
+#[no_mangle]
+pub extern "C" fn fb_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |m| m.as_ptr()))
+}
+
+/// decode the `len` bytes of the blueprint string at `data` into a new
+/// container
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes, and `out` to a writable
+/// pointer.
+#[no_mangle]
+pub unsafe extern "C" fn fb_decode(
+    data: *const c_char,
+    len: usize,
+    out: *mut *mut Container,
+) -> FbStatus {
+    if data.is_null() || out.is_null() {
+        return null_pointer();
+    }
+    let data = std::slice::from_raw_parts(data.cast::<u8>(), len);
+    run(|| {
+        let container = BlueprintCodec::decode(data)?;
+        *out = Box::into_raw(Box::new(container));
+        Ok(())
+    })
+}
+
+/// read the `len` bytes of json at `data` into a new container
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes, and `out` to a writable
+/// pointer.
+#[no_mangle]
+pub unsafe extern "C" fn fb_container_from_json(
+    data: *const c_char,
+    len: usize,
+    out: *mut *mut Container,
+) -> FbStatus {
+    if data.is_null() || out.is_null() {
+        return null_pointer();
+    }
+    let data = std::slice::from_raw_parts(data.cast::<u8>(), len);
+    run(|| {
+        let container = BlueprintCodec::deserialize_json(data)?;
+        *out = Box::into_raw(Box::new(container));
+        Ok(())
+    })
+}
+
+/// release a container; null is ignored
+///
+/// # Safety
+///
+/// `container` must be null or have been returned by this library, and not
+/// already freed.
+#[no_mangle]
+pub unsafe extern "C" fn fb_container_free(container: *mut Container) {
+    if !container.is_null() {
+        drop(Box::from_raw(container));
+    }
+}
+
+/// encode the container as a blueprint string, to be released with
+/// `fb_string_free`
+///
+/// # Safety
+///
+/// `container` must be a valid container, and `out` must point to a
+/// writable pointer.
+#[no_mangle]
+pub unsafe extern "C" fn fb_encode(container: *const Container, out: *mut *mut c_char) -> FbStatus {
+    if container.is_null() || out.is_null() {
+        return null_pointer();
+    }
+    run(|| {
+        *out = into_c_string(BlueprintCodec::encode_string(&*container)?);
+        Ok(())
+    })
+}
+
+/// serialize the container as json, to be released with `fb_string_free`
+///
+/// # Safety
+///
+/// `container` must be a valid container, and `out` must point to a
+/// writable pointer.
+#[no_mangle]
+pub unsafe extern "C" fn fb_container_to_json(
+    container: *const Container,
+    out: *mut *mut c_char,
+) -> FbStatus {
+    if container.is_null() || out.is_null() {
+        return null_pointer();
+    }
+    run(|| {
+        *out = into_c_string(serde_json::to_string(&*container)?);
+        Ok(())
+    })
+}
+
+/// serialize the entity as json, to be released with `fb_string_free`
+///
+/// # Safety
+///
+/// `entity` must be a valid entity, and `out` must point to a writable
+/// pointer.
+#[no_mangle]
+pub unsafe extern "C" fn fb_entity_to_json(
+    entity: *const Entity,
+    out: *mut *mut c_char,
+) -> FbStatus {
+    if entity.is_null() || out.is_null() {
+        return null_pointer();
+    }
+    run(|| {
+        *out = into_c_string(serde_json::to_string(&*entity)?);
+        Ok(())
+    })
+}
+
+/// release a string returned by this library; null is ignored
+///
+/// # Safety
+///
+/// `s` must be null or have been returned through an out parameter of this
+/// library, and not already freed.
+#[no_mangle]
+pub unsafe extern "C" fn fb_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// what the container holds
+///
+/// # Safety
+///
+/// `container` must be a valid container.
+#[no_mangle]
+pub unsafe extern "C" fn fb_container_kind(container: *const Container) -> FbContainerKind {
+    match &*container {
+        Container::BlueprintBook(_) => FbContainerKind::BlueprintBook,
+        Container::Blueprint(_) => FbContainerKind::Blueprint,
+        Container::DeconstructionPlanner(_) => FbContainerKind::DeconstructionPlanner,
+        Container::UpgradePlanner(_) => FbContainerKind::UpgradePlanner,
+    }
+}
+
+/// the label of whatever the container holds
+///
+/// # Safety
+///
+/// `container` must be a valid container.
+#[no_mangle]
+pub unsafe extern "C" fn fb_container_label(container: *const Container) -> FbStr {
+    FbStr::optional(match &*container {
+        Container::BlueprintBook(book) => book.label.as_deref(),
+        Container::Blueprint(blueprint) => blueprint.label.as_deref(),
+        Container::DeconstructionPlanner(planner) => planner.label.as_deref(),
+        Container::UpgradePlanner(planner) => planner.label.as_deref(),
+    })
+}
+
+/// the game version of whatever the container holds
+///
+/// # Safety
+///
+/// `container` must be a valid container.
+#[no_mangle]
+pub unsafe extern "C" fn fb_container_version(container: *const Container) -> u64 {
+    match &*container {
+        Container::BlueprintBook(book) => book.version,
+        Container::Blueprint(blueprint) => blueprint.version,
+        Container::DeconstructionPlanner(planner) => planner.version,
+        Container::UpgradePlanner(planner) => planner.version,
+    }
+}
+
+/// the book which the container holds, or null if it holds something else
+///
+/// # Safety
+///
+/// `container` must be a valid container.
+#[no_mangle]
+pub unsafe extern "C" fn fb_container_book(container: *const Container) -> *const BlueprintBook {
+    match &*container {
+        Container::BlueprintBook(book) => book,
+        _ => ptr::null(),
+    }
+}
+
+/// the blueprint which the container holds, or null if it holds something
+/// else
+///
+/// # Safety
+///
+/// `container` must be a valid container.
+#[no_mangle]
+pub unsafe extern "C" fn fb_container_blueprint(container: *const Container) -> *const Blueprint {
+    match &*container {
+        Container::Blueprint(blueprint) => blueprint,
+        _ => ptr::null(),
+    }
+}
+
+/// the number of pages in the book
+///
+/// # Safety
+///
+/// `book` must be a valid book.
+#[no_mangle]
+pub unsafe extern "C" fn fb_book_page_count(book: *const BlueprintBook) -> usize {
+    (*book).blueprints.len()
+}
+
+/// the page at position `n` of the book, or null if there are not that
+/// many pages
+///
+/// Pages are in the order in which the book stores them, which need not be
+/// the order of their indices; see `fb_book_page_index`.
+///
+/// # Safety
+///
+/// `book` must be a valid book.
+#[no_mangle]
+pub unsafe extern "C" fn fb_book_page(book: *const BlueprintBook, n: usize) -> *const Container {
+    let book = &*book;
+    book.blueprints
+        .get(n)
+        .map_or(ptr::null(), |page| &page.item)
+}
+
+/// the index of the page at position `n` of the book, or `SIZE_MAX` if
+/// there are not that many pages
+///
+/// This is the slot which the page occupies in the game, which is not
+/// necessarily its position.
+///
+/// # Safety
+///
+/// `book` must be a valid book.
+#[no_mangle]
+pub unsafe extern "C" fn fb_book_page_index(book: *const BlueprintBook, n: usize) -> usize {
+    let book = &*book;
+    book.blueprints.get(n).map_or(usize::MAX, |page| page.index)
+}
+
+/// the index of the page which is selected in the book
+///
+/// Compare it with `fb_book_page_index`, not with positions.
+///
+/// # Safety
+///
+/// `book` must be a valid book.
+#[no_mangle]
+pub unsafe extern "C" fn fb_book_active_index(book: *const BlueprintBook) -> usize {
+    (*book).active_index
+}
+
+/// the description of the blueprint
+///
+/// # Safety
+///
+/// `blueprint` must be a valid blueprint.
+#[no_mangle]
+pub unsafe extern "C" fn fb_blueprint_description(blueprint: *const Blueprint) -> FbStr {
+    FbStr::optional((*blueprint).description.as_deref())
+}
+
+/// the number of entities in the blueprint
+///
+/// # Safety
+///
+/// `blueprint` must be a valid blueprint.
+#[no_mangle]
+pub unsafe extern "C" fn fb_blueprint_entity_count(blueprint: *const Blueprint) -> usize {
+    (*blueprint).entities.len()
+}
+
+/// the entity at position `n` of the blueprint, or null if there are not
+/// that many entities
+///
+/// # Safety
+///
+/// `blueprint` must be a valid blueprint.
+#[no_mangle]
+pub unsafe extern "C" fn fb_blueprint_entity(
+    blueprint: *const Blueprint,
+    n: usize,
+) -> *const Entity {
+    let blueprint = &*blueprint;
+    blueprint.entities.get(n).map_or(ptr::null(), |e| e)
+}
+
+/// the number of tiles in the blueprint
+///
+/// # Safety
+///
+/// `blueprint` must be a valid blueprint.
+#[no_mangle]
+pub unsafe extern "C" fn fb_blueprint_tile_count(blueprint: *const Blueprint) -> usize {
+    (*blueprint).tiles.len()
+}
+
+/// the tile at position `n` of the blueprint, or null if there are not that
+/// many tiles
+///
+/// # Safety
+///
+/// `blueprint` must be a valid blueprint.
+#[no_mangle]
+pub unsafe extern "C" fn fb_blueprint_tile(blueprint: *const Blueprint, n: usize) -> *const Tile {
+    let blueprint = &*blueprint;
+    blueprint.tiles.get(n).map_or(ptr::null(), |t| t)
+}
+
+/// the number by which wires and schedules refer to the entity
+///
+/// # Safety
+///
+/// `entity` must be a valid entity.
+#[no_mangle]
+pub unsafe extern "C" fn fb_entity_number(entity: *const Entity) -> usize {
+    (*entity).entity_number.get()
+}
+
+/// the prototype name of the entity, such as `transport-belt`
+///
+/// # Safety
+///
+/// `entity` must be a valid entity.
+#[no_mangle]
+pub unsafe extern "C" fn fb_entity_name(entity: *const Entity) -> FbStr {
+    FbStr::new(&(*entity).name)
+}
+
+/// the position of the entity's center
+///
+/// # Safety
+///
+/// `entity` must be a valid entity.
+#[no_mangle]
+pub unsafe extern "C" fn fb_entity_position(entity: *const Entity) -> FbPosition {
+    FbPosition::from(&(*entity).position)
+}
+
+/// the direction of the entity, in eighths of a turn clockwise from north
+///
+/// # Safety
+///
+/// `entity` must be a valid entity.
+#[no_mangle]
+pub unsafe extern "C" fn fb_entity_direction(entity: *const Entity) -> u8 {
+    (*entity).direction.clone().unwrap_or(Direction::North) as u8
+}
+
+/// the recipe of the entity, if it is a machine with one set
+///
+/// # Safety
+///
+/// `entity` must be a valid entity.
+#[no_mangle]
+pub unsafe extern "C" fn fb_entity_recipe(entity: *const Entity) -> FbStr {
+    FbStr::optional((*entity).recipe.as_deref())
+}
+
+/// the prototype name of the tile, such as `stone-path`
+///
+/// # Safety
+///
+/// `tile` must be a valid tile.
+#[no_mangle]
+pub unsafe extern "C" fn fb_tile_name(tile: *const Tile) -> FbStr {
+    FbStr::new(&(*tile).name)
+}
+
+/// the position of the tile's top left corner
+///
+/// # Safety
+///
+/// `tile` must be a valid tile.
+#[no_mangle]
+pub unsafe extern "C" fn fb_tile_position(tile: *const Tile) -> FbPosition {
+    FbPosition::from(&(*tile).position)
+}
@@ -0,0 +1,193 @@
+//! Forms of a `Container` other than json and blueprint strings.
+//!
+//! YAML, TOML and RON are convenient for editing and reviewing blueprints
+//! by hand; MessagePack and CBOR store them compactly. Each is enabled by a
+//! feature of the same name (`msgpack` for MessagePack), and each goes
+//! through the same serde model as json, so a container survives a
+//! roundtrip through any of them unchanged. Lua tables, which need no
+//! feature, are described in [`lua`](crate::lua).
+
+use crate::{BlueprintCodec, Container, Error, Result};
+use std::fmt;
+use std::str::FromStr;
+
+/// A format which a [`Container`] can be converted to and from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// A Factorio blueprint string.
+    Blueprint,
+    Json,
+    /// A Lua table, as described in [`lua`](crate::lua).
+    Lua,
+    Yaml,
+    Toml,
+    Ron,
+    MessagePack,
+    Cbor,
+}
+
+impl Format {
+    pub const ALL: [Format; 8] = [
+        Format::Blueprint,
+        Format::Json,
+        Format::Lua,
+        Format::Yaml,
+        Format::Toml,
+        Format::Ron,
+        Format::MessagePack,
+        Format::Cbor,
+    ];
+
+    /// the name of this format, as accepted by `from_str`
+    pub fn name(self) -> &'static str {
+        match self {
+            Format::Blueprint => "blueprint",
+            Format::Json => "json",
+            Format::Lua => "lua",
+            Format::Yaml => "yaml",
+            Format::Toml => "toml",
+            Format::Ron => "ron",
+            Format::MessagePack => "msgpack",
+            Format::Cbor => "cbor",
+        }
+    }
+
+    /// the feature which enables this format, if it needs one
+    pub fn feature(self) -> Option<&'static str> {
+        match self {
+            Format::Blueprint | Format::Json | Format::Lua => None,
+            _ => Some(self.name()),
+        }
+    }
+
+    /// whether this format was enabled when the library was built
+    pub fn is_available(self) -> bool {
+        match self {
+            Format::Blueprint | Format::Json | Format::Lua => true,
+            Format::Yaml => cfg!(feature = "yaml"),
+            Format::Toml => cfg!(feature = "toml"),
+            Format::Ron => cfg!(feature = "ron"),
+            Format::MessagePack => cfg!(feature = "msgpack"),
+            Format::Cbor => cfg!(feature = "cbor"),
+        }
+    }
+
+    /// whether this format is binary, rather than text
+    pub fn is_binary(self) -> bool {
+        matches!(self, Format::MessagePack | Format::Cbor)
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(name: &str) -> std::result::Result<Format, String> {
+        match name.to_ascii_lowercase().as_str() {
+            "yml" => Ok(Format::Yaml),
+            "messagepack" | "mpk" => Ok(Format::MessagePack),
+            name => Format::ALL
+                .iter()
+                .copied()
+                .find(|format| format.name() == name)
+                .ok_or_else(|| {
+                    let names = Format::ALL.iter().map(|format| format.name());
+                    format!(
+                        "unknown format `{}`, expected one of {}",
+                        name,
+                        names.collect::<Vec<_>>().join(", ")
+                    )
+                }),
+        }
+    }
+}
+
+/// wrap an error from the library implementing `format`
+#[cfg(any(
+    feature = "yaml",
+    feature = "toml",
+    feature = "ron",
+    feature = "msgpack",
+    feature = "cbor"
+))]
+fn format_error<E>(format: Format) -> impl FnOnce(E) -> Error
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    move |source| Error::Format {
+        format,
+        source: Box::new(source),
+    }
+}
+
+impl Container {
+    /// serialize this container in the given format
+    ///
+    /// Text formats produce utf8. Blueprint strings are encoded with the
+    /// default options.
+    pub fn to_format(&self, format: Format) -> Result<Vec<u8>> {
+        match format {
+            Format::Blueprint => BlueprintCodec::encode_string(self).map(String::into_bytes),
+            Format::Json => Ok(serde_json::to_vec(self)?),
+            Format::Lua => crate::lua::to_string_pretty(self).map(String::into_bytes),
+            #[cfg(feature = "yaml")]
+            Format::Yaml => serde_yaml::to_string(self)
+                .map(String::into_bytes)
+                .map_err(format_error(format)),
+            #[cfg(feature = "toml")]
+            Format::Toml => toml::to_string(self)
+                .map(String::into_bytes)
+                .map_err(format_error(format)),
+            #[cfg(feature = "ron")]
+            Format::Ron => ron::ser::to_string_pretty(self, Default::default())
+                .map(String::into_bytes)
+                .map_err(format_error(format)),
+            // structs must be maps, not arrays, for the untagged enums to
+            // tell their variants apart
+            #[cfg(feature = "msgpack")]
+            Format::MessagePack => rmp_serde::to_vec_named(self).map_err(format_error(format)),
+            #[cfg(feature = "cbor")]
+            Format::Cbor => {
+                let mut out = Vec::new();
+                ciborium::ser::into_writer(self, &mut out).map_err(format_error(format))?;
+                Ok(out)
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(Error::FormatUnavailable(format)),
+        }
+    }
+
+    /// deserialize a container in the given format
+    pub fn from_format(data: &[u8], format: Format) -> Result<Container> {
+        crate::prototype::interned(|| match format {
+            Format::Blueprint => BlueprintCodec::decode(data),
+            Format::Json => BlueprintCodec::deserialize_json(data),
+            Format::Lua => {
+                crate::lua::from_str(std::str::from_utf8(data).map_err(|_| Error::LuaSyntax {
+                    line: 1,
+                    message: "input is not utf8".into(),
+                })?)
+            }
+            #[cfg(feature = "yaml")]
+            Format::Yaml => serde_yaml::from_slice(data).map_err(format_error(format)),
+            #[cfg(feature = "toml")]
+            Format::Toml => {
+                toml::from_str(std::str::from_utf8(data).map_err(format_error(format))?)
+                    .map_err(format_error(format))
+            }
+            #[cfg(feature = "ron")]
+            Format::Ron => ron::de::from_bytes(data).map_err(format_error(format)),
+            #[cfg(feature = "msgpack")]
+            Format::MessagePack => rmp_serde::from_slice(data).map_err(format_error(format)),
+            #[cfg(feature = "cbor")]
+            Format::Cbor => ciborium::de::from_reader(data).map_err(format_error(format)),
+            #[allow(unreachable_patterns)]
+            _ => Err(Error::FormatUnavailable(format)),
+        })
+    }
+}
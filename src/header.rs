@@ -0,0 +1,145 @@
+//! Lightweight decoding of container headers.
+//!
+//! Listing the contents of a large blueprint book does not require its
+//! entities. The headers in this module deserialize only labels, icons,
+//! versions, and the like; entity and tile arrays are skipped as they are
+//! parsed, and only their lengths are kept. Nothing else within a blueprint
+//! is materialized.
+
+use crate::{
+    objects::{deserialize_page, Color, Icon, PageField, PageItem},
+    BlueprintCodec,
+};
+use serde::{
+    de::{self, MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
+use std::fmt;
+use std::io::Read;
+
+/// The header of a [`Container`](crate::Container).
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContainerHeader {
+    BlueprintBook(BookHeader),
+    Blueprint(BlueprintHeader),
+    DeconstructionPlanner(PlannerHeader),
+    UpgradePlanner(PlannerHeader),
+}
+
+impl ContainerHeader {
+    pub fn decode<R: Read>(reader: R) -> crate::Result<Self> {
+        BlueprintCodec::decode_header(reader)
+    }
+
+    pub fn label(&self) -> Option<&str> {
+        match self {
+            ContainerHeader::BlueprintBook(book) => book.label.as_deref(),
+            ContainerHeader::Blueprint(blueprint) => blueprint.label.as_deref(),
+            ContainerHeader::DeconstructionPlanner(planner)
+            | ContainerHeader::UpgradePlanner(planner) => planner.label.as_deref(),
+        }
+    }
+}
+
+/// The header of a [`BlueprintBook`](crate::objects::BlueprintBook), with
+/// the headers of its pages.
+#[derive(Debug, PartialEq, Eq, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct BookHeader {
+    pub item: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label_color: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub blueprints: Vec<PageHeader>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub icons: Vec<Icon>,
+    pub active_index: usize,
+    pub version: u64,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+pub struct PageHeader {
+    pub index: usize,
+    #[serde(flatten)]
+    pub item: ContainerHeader,
+}
+
+impl<'de> Deserialize<'de> for PageHeader {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (index, item) = deserialize_page(deserializer)?;
+        Ok(PageHeader { index, item })
+    }
+}
+
+impl<'de> PageItem<'de> for ContainerHeader {
+    fn next_item<A: MapAccess<'de>>(field: PageField, map: &mut A) -> Result<Self, A::Error> {
+        Ok(match field {
+            PageField::BlueprintBook => ContainerHeader::BlueprintBook(map.next_value()?),
+            PageField::Blueprint => ContainerHeader::Blueprint(map.next_value()?),
+            PageField::DeconstructionPlanner => {
+                ContainerHeader::DeconstructionPlanner(map.next_value()?)
+            }
+            PageField::UpgradePlanner => ContainerHeader::UpgradePlanner(map.next_value()?),
+            PageField::Index | PageField::Other => unreachable!("not an item field"),
+        })
+    }
+}
+
+/// The header of a [`Blueprint`](crate::objects::Blueprint).
+#[derive(Debug, PartialEq, Eq, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct BlueprintHeader {
+    pub item: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label_color: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub icons: Vec<Icon>,
+    pub version: u64,
+    /// The number of entities, which were skipped.
+    #[serde(rename(deserialize = "entities"), deserialize_with = "count")]
+    pub entity_count: usize,
+    /// The number of tiles, which were skipped.
+    #[serde(rename(deserialize = "tiles"), deserialize_with = "count")]
+    pub tile_count: usize,
+}
+
+/// The header of a deconstruction or upgrade planner.
+#[derive(Debug, PartialEq, Eq, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PlannerHeader {
+    pub item: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub version: u64,
+}
+
+/// count the elements of a sequence without materializing them
+fn count<'de, D: Deserializer<'de>>(deserializer: D) -> Result<usize, D::Error> {
+    struct CountVisitor;
+
+    impl<'de> Visitor<'de> for CountVisitor {
+        type Value = usize;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a sequence")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut count = 0;
+            while seq.next_element::<de::IgnoredAny>()?.is_some() {
+                count += 1;
+            }
+            Ok(count)
+        }
+    }
+
+    deserializer.deserialize_seq(CountVisitor)
+}
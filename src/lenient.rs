@@ -0,0 +1,203 @@
+//! Clean up blueprint strings which were mangled on their way to us.
+//!
+//! People paste blueprint strings wrapped in quotes, inside markdown code
+//! fences, with URL-safe base64 characters, with missing `=` padding, or with
+//! the invisible characters which forums insert to wrap long lines. Strict
+//! decoding rejects all of those; [`normalize`] repairs them, and reports what
+//! it had to repair.
+
+use std::fmt;
+
+/// A repair made to a blueprint string before decoding it.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Repair {
+    /// Removed a surrounding markdown code fence or inline code span.
+    CodeFence,
+    /// Removed surrounding quotes.
+    Quotes,
+    /// Removed characters which cannot appear in a blueprint string, such as
+    /// zero-width spaces, non-breaking spaces, soft hyphens, and escaped
+    /// newlines.
+    StrayCharacters { count: usize },
+    /// Translated the URL-safe base64 characters `-` and `_` to `+` and `/`.
+    UrlSafeAlphabet,
+    /// Added missing `=` padding.
+    Padding { added: usize },
+}
+
+impl fmt::Display for Repair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Repair::CodeFence => write!(f, "removed code fence"),
+            Repair::Quotes => write!(f, "removed quotes"),
+            Repair::StrayCharacters { count } => write!(f, "removed {} stray characters", count),
+            Repair::UrlSafeAlphabet => write!(f, "translated url-safe base64"),
+            Repair::Padding { added } => write!(f, "added {} padding characters", added),
+        }
+    }
+}
+
+const QUOTES: &[(char, char)] = &[
+    ('"', '"'),
+    ('\'', '\''),
+    ('\u{201c}', '\u{201d}'),
+    ('\u{2018}', '\u{2019}'),
+];
+
+fn is_base64(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || ch == '+' || ch == '/' || ch == '='
+}
+
+fn is_url_safe(ch: char) -> bool {
+    ch == '-' || ch == '_'
+}
+
+/// remove a markdown code fence or inline code span around the input
+fn strip_code_fence(input: &str) -> Option<&str> {
+    if let Some(fenced) = input.strip_prefix("```") {
+        // the opening fence may name a language: skip to the end of its line
+        let body = fenced
+            .find('\n')
+            .map_or(fenced, |newline| &fenced[newline + 1..]);
+        return Some(body.strip_suffix("```").unwrap_or(body));
+    }
+    input.strip_prefix('`')?.strip_suffix('`')
+}
+
+fn strip_quotes(input: &str) -> Option<&str> {
+    QUOTES
+        .iter()
+        .find_map(|&(open, close)| input.strip_prefix(open)?.strip_suffix(close))
+}
+
+/// Repair a blueprint string, returning the repaired string and the repairs
+/// which were made.
+///
+/// ASCII whitespace is removed without being reported, as strict decoding
+/// also ignores it. Everything else which is not part of the standard or
+/// URL-safe base64 alphabets is removed and reported.
+pub fn normalize(input: &str) -> (String, Vec<Repair>) {
+    let mut repairs = Vec::new();
+
+    // fences and quotes may be nested in either order, so peel until stable
+    let mut body = input.trim();
+    loop {
+        if let Some(inner) = strip_code_fence(body) {
+            if !repairs.contains(&Repair::CodeFence) {
+                repairs.push(Repair::CodeFence);
+            }
+            body = inner.trim();
+        } else if let Some(inner) = strip_quotes(body) {
+            if !repairs.contains(&Repair::Quotes) {
+                repairs.push(Repair::Quotes);
+            }
+            body = inner.trim();
+        } else {
+            break;
+        }
+    }
+
+    let mut out = String::with_capacity(body.len());
+    let mut stray = 0;
+    let mut url_safe = false;
+    let mut chars = body.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            ch if ch.is_ascii_whitespace() => {}
+            ch if is_base64(ch) => out.push(ch),
+            ch if is_url_safe(ch) => {
+                url_safe = true;
+                out.push(if ch == '-' { '+' } else { '/' });
+            }
+            // escaped newlines, as in a string copied out of json or code
+            '\\' if matches!(chars.peek(), Some('n') | Some('r') | Some('t')) => {
+                chars.next();
+                stray += 2;
+            }
+            _ => stray += 1,
+        }
+    }
+    if stray > 0 {
+        repairs.push(Repair::StrayCharacters { count: stray });
+    }
+    if url_safe {
+        repairs.push(Repair::UrlSafeAlphabet);
+    }
+
+    // the version byte is not part of the base64 payload
+    let payload = out.len().saturating_sub(1);
+    let unpadded = out.trim_end_matches('=').len().saturating_sub(1);
+    if payload == unpadded {
+        let added = match unpadded % 4 {
+            2 => 2,
+            3 => 1,
+            _ => 0,
+        };
+        if added > 0 {
+            out.push_str(&"=="[..added]);
+            repairs.push(Repair::Padding { added });
+        }
+    }
+
+    (out, repairs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize, Repair};
+
+    #[test]
+    fn clean_input_needs_no_repair() {
+        assert_eq!(normalize(" 0eNqrVg==\n"), ("0eNqrVg==".into(), vec![]));
+    }
+
+    #[test]
+    fn code_fences() {
+        assert_eq!(
+            normalize("```text\n0eNqrVg==\n```"),
+            ("0eNqrVg==".into(), vec![Repair::CodeFence])
+        );
+        assert_eq!(
+            normalize("`0eNqrVg==`"),
+            ("0eNqrVg==".into(), vec![Repair::CodeFence])
+        );
+    }
+
+    #[test]
+    fn nested_quotes_and_fences() {
+        assert_eq!(
+            normalize("```\n\"0eNqrVg==\"\n```"),
+            ("0eNqrVg==".into(), vec![Repair::CodeFence, Repair::Quotes])
+        );
+        assert_eq!(
+            normalize("\u{201c}`0eNqrVg==`\u{201d}"),
+            ("0eNqrVg==".into(), vec![Repair::Quotes, Repair::CodeFence])
+        );
+    }
+
+    #[test]
+    fn forum_artifacts() {
+        assert_eq!(
+            normalize("0eNq\u{200b}rV\u{00a0}g=\\n="),
+            (
+                "0eNqrVg==".into(),
+                vec![Repair::StrayCharacters { count: 4 }]
+            )
+        );
+    }
+
+    #[test]
+    fn url_safe_and_padding() {
+        assert_eq!(
+            normalize("0ab-_cd"),
+            (
+                "0ab+/cd==".into(),
+                vec![Repair::UrlSafeAlphabet, Repair::Padding { added: 2 }]
+            )
+        );
+        assert_eq!(
+            normalize("0abc"),
+            ("0abc=".into(), vec![Repair::Padding { added: 1 }])
+        );
+    }
+}
@@ -1,19 +1,56 @@
 use base64::{read::DecoderReader as Base64Decoder, write::EncoderWriter as Base64Encoder};
+use blueprint_stream::BlueprintStream;
+use encode_options::EncodeOptions;
 use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+pub use formats::Format;
+use header::ContainerHeader;
+use lenient::Repair;
+use limits::{DecodeLimits, LimitedReader};
+use line_wrapper::LineWrapper;
 use objects::{Blueprint, BlueprintBook, DeconstructionPlanner, UpgradePlanner};
-use serde::{Deserialize, Serialize};
+use pages::Pages;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+pub use stage::Stage;
 use std::io::prelude::*;
+use std::ops::Range;
 use thiserror::Error;
 use version_prefix::{VersionPrefixReader, VersionPrefixWriter};
 use whitespace_remover::WhitespaceRemover;
 
+#[cfg(feature = "async")]
+pub mod async_codec;
+pub mod blueprint_stream;
+pub mod encode_options;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod formats;
+pub mod header;
+pub mod lenient;
+pub mod limits;
+pub mod line_wrapper;
+pub mod lua;
 pub mod objects;
+pub mod optimize;
+pub mod pages;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+mod prototype;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "qr")]
+pub mod qr;
+mod scan;
+pub mod split;
+mod stage;
 pub mod version_prefix;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 pub mod whitespace_remover;
 
 /// `Container`s are the primary entry point for this library: they contain
 /// either a single blueprint, or a blueprint book.
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum Container {
     BlueprintBook(BlueprintBook),
@@ -35,6 +72,17 @@ impl From<Blueprint> for Container {
 }
 
 impl Container {
+    /// Put this container into canonical form, in which it encodes
+    /// identically to any other container which differs only in the order
+    /// of its entities, tiles, icons, or book pages.
+    pub fn canonicalize(&mut self) {
+        match self {
+            Container::BlueprintBook(book) => book.canonicalize(),
+            Container::Blueprint(blueprint) => blueprint.canonicalize(),
+            Container::DeconstructionPlanner(_) | Container::UpgradePlanner(_) => {}
+        }
+    }
+
     pub fn decode<R: Read>(reader: R) -> Result<Self> {
         BlueprintCodec::decode(reader)
     }
@@ -42,24 +90,257 @@ impl Container {
     pub fn encode<W: Write>(&self, writer: W) -> Result<()> {
         BlueprintCodec::encode(writer, self)
     }
+
+    pub fn encode_with<W: Write>(&self, writer: W, options: &EncodeOptions) -> Result<()> {
+        BlueprintCodec::encode_with(writer, self, options)
+    }
+
+    #[cfg(feature = "async")]
+    pub async fn decode_async<R>(reader: R) -> Result<Self>
+    where
+        R: tokio::io::AsyncRead + Unpin,
+    {
+        BlueprintCodec::decode_async(reader).await
+    }
+
+    #[cfg(feature = "async")]
+    pub async fn encode_async<W>(&self, writer: W) -> Result<()>
+    where
+        W: tokio::io::AsyncWrite + Unpin,
+    {
+        BlueprintCodec::encode_async(writer, self).await
+    }
+
+    #[cfg(feature = "async")]
+    pub async fn encode_async_with<W>(&self, writer: W, options: &EncodeOptions) -> Result<()>
+    where
+        W: tokio::io::AsyncWrite + Unpin,
+    {
+        BlueprintCodec::encode_async_with(writer, self, options).await
+    }
 }
 
+/// Errors which can occur while encoding or decoding blueprint strings.
+///
+/// Errors which occur while decoding name the stage of the pipeline which
+/// failed. Offsets are byte offsets into the input, counting any whitespace.
 #[derive(Error, Debug)]
 pub enum Error {
-    #[error("json problem")]
-    Json(#[from] serde_json::Error),
+    #[error("json error at `{path}`: {source}")]
+    Json {
+        /// path to the offending value, e.g. `blueprint.entities[17].name`
+        path: String,
+        source: serde_json::Error,
+    },
     #[error("failed to write valid utf8")]
     Utf8(#[from] std::string::FromUtf8Error),
-    #[error("io troubles; probably transient")]
+    #[error("io error: {0}")]
     Io(#[from] std::io::Error),
-    #[error("unexpected blueprint string version byte")]
-    UnknownVersion,
+    #[error("failed to read input at byte {offset}: {source}")]
+    Input { offset: u64, source: std::io::Error },
+    #[error(
+        "unexpected blueprint string version byte {:?} at byte {offset}",
+        char::from(*.found)
+    )]
+    UnknownVersion { found: u8, offset: u64 },
+    #[error("invalid base64 at byte {offset}: {source}")]
+    Base64 {
+        offset: u64,
+        source: base64::DecodeError,
+    },
+    /// The offset is approximate: it is the position in the input of the
+    /// compressed data which the decompressor had consumed when it failed.
+    #[error("corrupt zlib stream near byte {offset}: {source}")]
+    Zlib { offset: u64, source: std::io::Error },
     #[error("failed to read any data")]
     NoData,
+    #[error("input exceeds the limit of {limit} bytes")]
+    InputTooLarge { limit: u64 },
+    #[error("decompressed data exceeds the limit of {limit} bytes")]
+    DecompressedTooLarge { limit: u64 },
+    #[error("blueprint books are nested more than {limit} deep")]
+    BooksTooDeep { limit: usize },
+    #[error("blueprints contain more than {limit} entities")]
+    TooManyEntities { limit: usize },
+    #[error("compression level {level} is not between 0 and 9")]
+    CompressionLevel { level: u32 },
+    #[error("version byte {0:?} is not ascii")]
+    VersionByte(char),
+    #[error("cannot write {found} after {after}")]
+    StreamOrder {
+        found: &'static str,
+        after: &'static str,
+    },
+    #[error("{format} error: {source}")]
+    Format {
+        format: Format,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    #[error("{} support requires the `{}` feature", .0, .0.feature().unwrap_or_default())]
+    FormatUnavailable(Format),
+    #[error("lua syntax error on line {line}: {message}")]
+    LuaSyntax { line: usize, message: String },
+    #[error("lua value error at `{path}`: {message}")]
+    LuaValue {
+        /// path to the offending value, e.g. `blueprint.entities[17].name`
+        path: String,
+        message: String,
+    },
+    #[cfg(feature = "wasm")]
+    #[error("javascript value error at `{path}`: {message}")]
+    JsValue {
+        /// path to the offending value, e.g. `blueprint.entities[17].name`
+        path: String,
+        message: String,
+    },
+    #[error("page at {path:?} encodes to {len} bytes on its own, over the limit of {limit}")]
+    PageTooLarge {
+        /// positions of the page and of its enclosing books, outermost first
+        path: Vec<usize>,
+        len: usize,
+        limit: usize,
+    },
+    #[error(
+        "book at {path:?} encodes to {len} bytes without any pages, over the limit of {limit}"
+    )]
+    BookTooLarge {
+        /// positions of the book and of its enclosing books, outermost first
+        path: Vec<usize>,
+        len: usize,
+        limit: usize,
+    },
+    #[cfg(feature = "qr")]
+    #[error("qr code error: {0}")]
+    Qr(String),
+    #[cfg(feature = "qr")]
+    #[error("qr code {part} of {count} is missing")]
+    QrMissing { part: usize, count: usize },
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(source: serde_json::Error) -> Error {
+        Error::Json {
+            path: ".".into(),
+            source,
+        }
+    }
+}
+
+impl From<serde_path_to_error::Error<serde_json::Error>> for Error {
+    fn from(err: serde_path_to_error::Error<serde_json::Error>) -> Error {
+        Error::Json {
+            path: err.path().to_string(),
+            source: err.into_inner(),
+        }
+    }
+}
+
+impl Error {
+    /// the stage of the decoding pipeline which produced this error, if any
+    pub fn stage(&self) -> Option<Stage> {
+        match self {
+            Error::Json { .. } => Some(Stage::Json),
+            Error::Input { .. } | Error::NoData => Some(Stage::Input),
+            Error::UnknownVersion { .. } => Some(Stage::Version),
+            Error::Base64 { .. } => Some(Stage::Base64),
+            Error::Zlib { .. } => Some(Stage::Zlib),
+            Error::InputTooLarge { .. } => Some(Stage::Input),
+            Error::DecompressedTooLarge { .. } => Some(Stage::Zlib),
+            Error::BooksTooDeep { .. } | Error::TooManyEntities { .. } => Some(Stage::Json),
+            Error::Utf8(_) | Error::Io(_) => None,
+            Error::CompressionLevel { .. } | Error::VersionByte(_) => None,
+            Error::Format { .. }
+            | Error::FormatUnavailable(_)
+            | Error::LuaSyntax { .. }
+            | Error::LuaValue { .. }
+            | Error::PageTooLarge { .. }
+            | Error::BookTooLarge { .. }
+            | Error::StreamOrder { .. } => None,
+            #[cfg(feature = "wasm")]
+            Error::JsValue { .. } => None,
+            #[cfg(feature = "qr")]
+            Error::Qr(_) | Error::QrMissing { .. } => None,
+        }
+    }
+
+    /// convert an io error which emerged from the decoding pipeline, using
+    /// the whitespace remover to map offsets back into the input
+    pub(crate) fn from_decoding<R>(err: std::io::Error, input: &WhitespaceRemover<R>) -> Error {
+        use base64::DecodeError;
+
+        // whitespace-free offsets start with the version byte; base64 offsets
+        // start just after it.
+        let base64_to_input = |offset: u64| input.input_offset(offset + 1);
+
+        let tagged = match stage::untag(err) {
+            Ok(tagged) => tagged,
+            Err(err) => return Error::Io(err),
+        };
+        match tagged.stage {
+            Stage::Input => Error::Input {
+                offset: tagged.offset,
+                source: tagged.source,
+            },
+            Stage::Base64 => {
+                let source = *tagged
+                    .source
+                    .into_inner()
+                    .and_then(|inner| inner.downcast::<DecodeError>().ok())
+                    .expect("base64 errors are only tagged when they wrap a DecodeError");
+                let offset = match source {
+                    DecodeError::InvalidByte(offset, _)
+                    | DecodeError::InvalidLastSymbol(offset, _) => offset as u64,
+                    DecodeError::InvalidLength => input.emitted().saturating_sub(2),
+                };
+                Error::Base64 {
+                    offset: base64_to_input(offset),
+                    source,
+                }
+            }
+            // zlib tags carry the number of compressed bytes consumed
+            _ => Error::Zlib {
+                // four base64 characters encode three bytes
+                offset: base64_to_input(tagged.offset / 3 * 4),
+                source: tagged.source,
+            },
+        }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Reader adaptor which yields the json payload of a blueprint string.
+///
+/// Produced by [`BlueprintCodec::decode_reader`]. Errors it produces remember
+/// which stage of the pipeline they came from.
+pub struct DecodeReader<'a, R>
+where
+    R: Read,
+{
+    inner: ZlibDecoder<Base64Decoder<'a, VersionPrefixReader<WhitespaceRemover<R>>>>,
+}
+
+impl<'a, R> DecodeReader<'a, R>
+where
+    R: Read,
+{
+    /// the number of compressed bytes consumed so far
+    pub fn total_in(&self) -> u64 {
+        self.inner.total_in()
+    }
+}
+
+impl<'a, R> Read for DecodeReader<'a, R>
+where
+    R: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.inner
+            .read(buf)
+            .map_err(|err| stage::tag_decompression(self.total_in(), err))
+    }
+}
+
 /// Utility class which knows how to convert JSON to and from Factorio's blueprint string format.
 pub struct BlueprintCodec;
 
@@ -71,17 +352,35 @@ impl BlueprintCodec {
     pub fn encode_writer<W, F>(writer: W, inner: F) -> Result<()>
     where
         W: Write,
-        F: FnOnce(ZlibEncoder<&mut Base64Encoder<VersionPrefixWriter<W>>>) -> std::io::Result<()>,
+        F: FnOnce(
+            ZlibEncoder<&mut Base64Encoder<VersionPrefixWriter<LineWrapper<W>>>>,
+        ) -> std::io::Result<()>,
     {
-        // the final step before sending the data out is to prepend a 0.
-        let mut writer = VersionPrefixWriter::new('0', writer);
-        // before we prepend that 0, we need to base64-encode the stream
+        Self::encode_writer_with(writer, &EncodeOptions::default(), inner)
+    }
+
+    /// writer adaptor which encodes json data to blueprint string format,
+    /// according to the given options
+    pub fn encode_writer_with<W, F>(writer: W, options: &EncodeOptions, inner: F) -> Result<()>
+    where
+        W: Write,
+        F: FnOnce(
+            ZlibEncoder<&mut Base64Encoder<VersionPrefixWriter<LineWrapper<W>>>>,
+        ) -> std::io::Result<()>,
+    {
+        options.validate()?;
+        // the very last step is to wrap lines, if requested
+        let writer = LineWrapper::new(options.line_width, writer);
+        // before that, we need to prepend the version byte
+        let mut writer = VersionPrefixWriter::new(options.version, writer);
+        // before we prepend that version, we need to base64-encode the stream
         let mut writer = Base64Encoder::new(&mut writer, base64::STANDARD);
         // note: we can't just hand this off, because we'll need to call its
         // `finish` method later
         {
             // before we base64 it, we should compress it
-            let writer = ZlibEncoder::new(writer.by_ref(), Compression::new(9));
+            let writer =
+                ZlibEncoder::new(writer.by_ref(), Compression::new(options.compression_level));
             // hand it off to the inner closure
             inner(writer)?;
         }
@@ -90,16 +389,86 @@ impl BlueprintCodec {
 
     /// write the blueprint string to the given writer
     pub fn encode<W: Write>(writer: W, container: &Container) -> Result<()> {
-        Self::encode_writer(writer, |writer| {
+        Self::encode_with(writer, container, &EncodeOptions::default())
+    }
+
+    /// write the blueprint string to the given writer, according to the
+    /// given options
+    pub fn encode_with<W: Write>(
+        writer: W,
+        container: &Container,
+        options: &EncodeOptions,
+    ) -> Result<()> {
+        let canonical;
+        let container = if options.canonical {
+            let mut container = container.clone();
+            container.canonicalize();
+            canonical = container;
+            &canonical
+        } else {
+            container
+        };
+        Self::encode_writer_with(writer, options, |writer| {
             // actually write this struct to the stream
             serde_json::to_writer(writer, container).map_err(|e| e.into())
         })
     }
 
+    /// write a blueprint string to the given writer, streaming the
+    /// blueprint's entities, tiles, and schedules from a closure
+    ///
+    /// See [`blueprint_stream`] for details.
+    pub fn encode_blueprint_stream<W, F>(writer: W, header: &Blueprint, inner: F) -> Result<()>
+    where
+        W: Write,
+        F: for<'a> FnOnce(&mut BlueprintStream<&'a mut dyn Write>) -> Result<()>,
+    {
+        Self::encode_blueprint_stream_with(writer, header, &EncodeOptions::default(), inner)
+    }
+
+    /// as `encode_blueprint_stream`, according to the given options
+    ///
+    /// `options.canonical` is ignored, as putting a blueprint in canonical
+    /// form requires all of it at once.
+    pub fn encode_blueprint_stream_with<W, F>(
+        writer: W,
+        header: &Blueprint,
+        options: &EncodeOptions,
+        inner: F,
+    ) -> Result<()>
+    where
+        W: Write,
+        F: for<'a> FnOnce(&mut BlueprintStream<&'a mut dyn Write>) -> Result<()>,
+    {
+        let mut out = Ok(());
+        let result = Self::encode_writer_with(writer, options, |mut writer| {
+            let result = BlueprintStream::new(&mut writer as &mut dyn Write, header)
+                .and_then(|mut stream| {
+                    inner(&mut stream)?;
+                    stream.finish()
+                })
+                .map(|_| ());
+            match result {
+                Err(Error::Io(err)) => Err(err),
+                result => {
+                    out = result;
+                    writer.finish().map(|_| ())
+                }
+            }
+        });
+        result.and(out)
+    }
+
     /// produce a new owned string containing the blueprint string
     pub fn encode_string(container: &Container) -> Result<String> {
+        Self::encode_string_with(container, &EncodeOptions::default())
+    }
+
+    /// produce a new owned string containing the blueprint string, encoded
+    /// according to the given options
+    pub fn encode_string_with(container: &Container, options: &EncodeOptions) -> Result<String> {
         let mut out = Vec::new();
-        Self::encode(&mut out, container)?;
+        Self::encode_with(&mut out, container, options)?;
         String::from_utf8(out).map_err(|e| e.into())
     }
 
@@ -110,9 +479,7 @@ impl BlueprintCodec {
     pub fn decode_reader<R, F>(reader: R, inner: F) -> Result<()>
     where
         R: Read,
-        F: FnOnce(
-            ZlibDecoder<Base64Decoder<VersionPrefixReader<WhitespaceRemover<R>>>>,
-        ) -> std::io::Result<()>,
+        F: FnOnce(DecodeReader<'_, R>) -> std::io::Result<()>,
     {
         // first, get rid of all whitespace. We know that the blueprint is
         // base64-encoded, and that character set has no whitespace, so this
@@ -122,32 +489,194 @@ impl BlueprintCodec {
         let mut reader = VersionPrefixReader::new('0', reader);
         // note: we can't just hand this off, because we'll need to call its
         // `had_expected_version` method later
-        {
+        let result = {
             // decode base64
             let reader = Base64Decoder::new(reader.by_ref(), base64::STANDARD);
             // decompress it
             let reader = ZlibDecoder::new(reader);
             // hand it off to the inner closure
-            inner(reader)?;
+            inner(DecodeReader { inner: reader })
+        };
+
+        Self::check_decoding(result, &reader)
+    }
+
+    /// turn the result of running the decoding pipeline into a diagnostic
+    ///
+    /// The version byte can only be checked once the pipeline has run; a bad
+    /// version is reported in preference to any error it caused downstream.
+    pub(crate) fn check_decoding<T, R>(
+        result: std::io::Result<T>,
+        reader: &VersionPrefixReader<WhitespaceRemover<R>>,
+    ) -> Result<T> {
+        let input = reader.get_ref();
+        let result = result.map_err(|err| Error::from_decoding(err, input));
+        // failing to read the input at all trumps everything else
+        if let Err(err @ Error::Input { .. }) = result {
+            return Err(err);
         }
         if !reader.had_expected_version().ok_or(Error::NoData)? {
-            Err(Error::UnknownVersion)?;
+            return Err(Error::UnknownVersion {
+                found: reader.found_version().expect("version was read"),
+                offset: input.input_offset(0),
+            });
         }
-        Ok(())
+        result
     }
 
     /// read the blueprint string from the given reader
+    ///
+    /// This applies no limits: use `decode_with_limits` for untrusted input.
     pub fn decode<R: Read>(reader: R) -> Result<Container> {
+        Self::decode_with_limits(reader, &DecodeLimits::unlimited())
+    }
+
+    /// read the blueprint string from the given reader, failing if it
+    /// exceeds any of the given limits
+    pub fn decode_with_limits<R: Read>(reader: R, limits: &DecodeLimits) -> Result<Container> {
+        Self::decode_measured(reader, limits).map(|(container, _)| container)
+    }
+
+    /// as `decode_with_limits`, but also report the number of compressed
+    /// bytes which made up the blueprint string
+    ///
+    /// Anything in the input after the end of the zlib stream is ignored,
+    /// so this is how to find out where the blueprint string ended.
+    pub(crate) fn decode_measured<R: Read>(
+        reader: R,
+        limits: &DecodeLimits,
+    ) -> Result<(Container, u64)> {
+        let reader = LimitedReader::new(reader, limits.max_input_bytes, |limit| {
+            Error::InputTooLarge { limit }
+        });
         let mut out = Err(Error::NoData);
-        Self::decode_reader(reader, |reader| {
-            out = serde_json::from_reader(reader).map_err(|e| e.into());
-            Ok(())
+        let mut compressed = 0;
+        let result = Self::decode_reader(reader, |mut reader| {
+            let limited = LimitedReader::new(&mut reader, limits.max_decompressed_bytes, |limit| {
+                Error::DecompressedTooLarge { limit }
+            });
+            match Self::deserialize_json(limited) {
+                // hand io errors back to `decode_reader`, which knows which
+                // stage produced them
+                Err(Error::Io(err)) => Err(err),
+                result => {
+                    out = result;
+                    compressed = reader.total_in();
+                    Ok(())
+                }
+            }
+        });
+        let container = result.and(out).map_err(limits::unwrap_exceeded)?;
+        limits.check(&container)?;
+        Ok((container, compressed))
+    }
+
+    /// read only the header of the container in the given reader
+    ///
+    /// See [`header`] for what is and is not decoded.
+    pub fn decode_header<R: Read>(reader: R) -> Result<ContainerHeader> {
+        let mut out = Err(Error::NoData);
+        let result = Self::decode_reader(reader, |reader| match Self::deserialize_json(reader) {
+            Err(Error::Io(err)) => Err(err),
+            result => {
+                out = result;
+                Ok(())
+            }
+        });
+        result.and(out)
+    }
+
+    /// stream the pages of the blueprint book in the given reader to a
+    /// closure, without decoding the whole book
+    ///
+    /// See [`pages`] for details. Errors which the closure returns are
+    /// returned from this method, with their pipeline stage attached.
+    pub fn decode_pages<R, F>(reader: R, inner: F) -> Result<()>
+    where
+        R: Read,
+        F: FnOnce(&mut Pages<DecodeReader<'_, R>>) -> Result<()>,
+    {
+        let mut out = Ok(());
+        let result = Self::decode_reader(reader, |reader| {
+            match inner(&mut Pages::new(reader)) {
+                // hand io errors back to `decode_reader`, which knows which
+                // stage produced them
+                Err(Error::Io(err)) => Err(err),
+                result => {
+                    out = result;
+                    Ok(())
+                }
+            }
+        });
+        result.and(out)
+    }
+
+    /// deserialize json from a reader, keeping track of the path to any
+    /// value which fails to deserialize
+    ///
+    /// io errors are returned as `Error::Io`.
+    ///
+    /// With the `simd-json` feature, the whole payload is read and parsed
+    /// with simd-json first. If that fails, it is parsed again as usual, which
+    /// reports any error in detail.
+    pub(crate) fn deserialize_json<R: Read, T: DeserializeOwned>(reader: R) -> Result<T> {
+        prototype::interned(|| {
+            #[cfg(feature = "simd-json")]
+            {
+                let mut reader = reader;
+                let mut json = Vec::new();
+                reader.read_to_end(&mut json)?;
+                // simd-json parses in place, so the original must be kept for
+                // the fallback
+                if let Ok(value) = simd_json::serde::from_slice(&mut json.clone()) {
+                    return Ok(value);
+                }
+                Self::deserialize_json_streaming(json.as_slice())
+            }
+            #[cfg(not(feature = "simd-json"))]
+            Self::deserialize_json_streaming(reader)
+        })
+    }
+
+    fn deserialize_json_streaming<R: Read, T: DeserializeOwned>(reader: R) -> Result<T> {
+        let mut deserializer = serde_json::Deserializer::from_reader(reader);
+        let value = serde_path_to_error::deserialize(&mut deserializer).map_err(|err| {
+            if err.inner().is_io() {
+                Error::Io(err.into_inner().into())
+            } else {
+                err.into()
+            }
         })?;
-        out
+        deserializer.end()?;
+        Ok(value)
     }
 
     /// read the blueprint string from the given input
     pub fn decode_string(blueprint: &str) -> Result<Container> {
         Self::decode(blueprint.as_bytes())
     }
+
+    /// read a blueprint string which may have been mangled in transit,
+    /// reporting the repairs which were required
+    ///
+    /// See [`lenient::normalize`] for the repairs which are attempted. Offsets
+    /// in errors refer to the repaired string.
+    pub fn decode_lenient(blueprint: &str) -> Result<(Container, Vec<Repair>)> {
+        let (blueprint, repairs) = lenient::normalize(blueprint);
+        Self::decode_string(&blueprint).map(|container| (container, repairs))
+    }
+
+    /// find every blueprint string in some arbitrary text, and try to decode
+    /// each of them
+    ///
+    /// Candidates are runs of base64 starting with `0eN`, or its equivalent
+    /// at other compression levels, which may be wrapped over several lines.
+    /// Each is decoded with the default [`DecodeLimits`], as scraped text is
+    /// rarely trustworthy. The range of each candidate within `text` is
+    /// returned alongside the result of decoding it; candidates which turn
+    /// out not to be blueprint strings produce errors, which callers will
+    /// usually want to skip.
+    pub fn find_all(text: &str) -> impl Iterator<Item = (Range<usize>, Result<Container>)> + '_ {
+        scan::find_all(text)
+    }
 }
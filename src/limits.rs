@@ -0,0 +1,163 @@
+//! Limits which protect against hostile blueprint strings.
+//!
+//! Blueprint strings are compressed, so a tiny string can expand to
+//! gigabytes of json. When decoding strings from untrusted sources, use
+//! [`BlueprintCodec::decode_with_limits`](crate::BlueprintCodec::decode_with_limits).
+
+use crate::{Container, Error, Result};
+use std::io::{self, Read};
+
+/// Limits applied while decoding a blueprint string.
+///
+/// Exceeding any limit produces a distinct [`Error`](enum@crate::Error) variant. The size limits
+/// are enforced while streaming, so decoding stops as soon as one is
+/// exceeded; the decompressed size limit therefore also bounds the memory
+/// used for the decoded objects. The structural limits are checked once the
+/// objects have been decoded.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct DecodeLimits {
+    /// Maximum size of the blueprint string in bytes, including whitespace.
+    pub max_input_bytes: u64,
+    /// Maximum size of the decompressed json in bytes.
+    pub max_decompressed_bytes: u64,
+    /// Maximum nesting depth of blueprint books. A book which contains only
+    /// blueprints has depth 1; a lone blueprint has depth 0.
+    pub max_book_depth: usize,
+    /// Maximum number of entities, summed over every blueprint.
+    pub max_entities: usize,
+}
+
+impl Default for DecodeLimits {
+    /// Limits generous enough for the largest books in common circulation.
+    fn default() -> DecodeLimits {
+        DecodeLimits {
+            max_input_bytes: 16 * 1024 * 1024,
+            max_decompressed_bytes: 128 * 1024 * 1024,
+            max_book_depth: 16,
+            max_entities: 1_000_000,
+        }
+    }
+}
+
+impl DecodeLimits {
+    /// No limits at all: suitable only for trusted input.
+    pub fn unlimited() -> DecodeLimits {
+        DecodeLimits {
+            max_input_bytes: u64::MAX,
+            max_decompressed_bytes: u64::MAX,
+            max_book_depth: usize::MAX,
+            max_entities: usize::MAX,
+        }
+    }
+
+    /// check the structural limits of a decoded container
+    pub fn check(&self, container: &Container) -> Result<()> {
+        let mut entities = 0;
+        self.check_inner(container, 0, &mut entities)
+    }
+
+    fn check_inner(&self, container: &Container, depth: usize, entities: &mut usize) -> Result<()> {
+        match container {
+            Container::BlueprintBook(book) => {
+                if depth >= self.max_book_depth {
+                    return Err(Error::BooksTooDeep {
+                        limit: self.max_book_depth,
+                    });
+                }
+                for page in &book.blueprints {
+                    self.check_inner(&page.item, depth + 1, entities)?;
+                }
+            }
+            Container::Blueprint(blueprint) => {
+                *entities += blueprint.entities.len();
+                if *entities > self.max_entities {
+                    return Err(Error::TooManyEntities {
+                        limit: self.max_entities,
+                    });
+                }
+            }
+            Container::DeconstructionPlanner(_) | Container::UpgradePlanner(_) => {}
+        }
+        Ok(())
+    }
+}
+
+/// Reader adaptor which fails once more than `limit` bytes have been read.
+///
+/// The error it produces wraps the [`Error`] returned by `exceeded`, which
+/// [`unwrap_exceeded`] recovers.
+pub(crate) struct LimitedReader<R> {
+    inner: R,
+    read: u64,
+    limit: u64,
+    exceeded: fn(u64) -> Error,
+}
+
+impl<R> LimitedReader<R> {
+    pub fn new(inner: R, limit: u64, exceeded: fn(u64) -> Error) -> LimitedReader<R> {
+        LimitedReader {
+            inner,
+            read: 0,
+            limit,
+            exceeded,
+        }
+    }
+}
+
+impl<R> Read for LimitedReader<R>
+where
+    R: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read += n as u64;
+        if self.read > self.limit {
+            return Err(io::Error::other((self.exceeded)(self.limit)));
+        }
+        Ok(n)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<R> tokio::io::AsyncRead for LimitedReader<R>
+where
+    R: tokio::io::AsyncRead + Unpin,
+{
+    fn poll_read(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> std::task::Poll<io::Result<()>> {
+        use std::task::Poll;
+
+        let this = self.get_mut();
+        let start = buf.filled().len();
+        match std::pin::Pin::new(&mut this.inner).poll_read(cx, buf) {
+            Poll::Ready(Ok(())) => {}
+            other => return other,
+        }
+        this.read += (buf.filled().len() - start) as u64;
+        if this.read > this.limit {
+            // a read which fails must not have filled anything
+            buf.set_filled(start);
+            return Poll::Ready(Err(io::Error::other((this.exceeded)(this.limit))));
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+pub(crate) fn is_exceeded(err: &io::Error) -> bool {
+    err.get_ref().is_some_and(|inner| inner.is::<Error>())
+}
+
+/// recover the error produced by a `LimitedReader`, wherever in the
+/// pipeline it emerged
+pub(crate) fn unwrap_exceeded(err: Error) -> Error {
+    match err {
+        Error::Input { source, .. } | Error::Io(source) if is_exceeded(&source) => *source
+            .into_inner()
+            .and_then(|inner| inner.downcast::<Error>().ok())
+            .expect("limit errors wrap an Error"),
+        err => err,
+    }
+}
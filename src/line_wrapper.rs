@@ -0,0 +1,134 @@
+use std::io::{Result, Write};
+
+/// Insert a newline into an outgoing stream every `width` bytes.
+///
+/// This is the inverse of [`WhitespaceRemover`](crate::whitespace_remover::WhitespaceRemover).
+/// No newline is written after the final line. A width of `None` passes data
+/// through unchanged.
+pub struct LineWrapper<W> {
+    inner: W,
+    width: Option<usize>,
+    column: usize,
+}
+
+impl<W> LineWrapper<W> {
+    pub fn new(width: Option<usize>, inner: W) -> LineWrapper<W> {
+        LineWrapper {
+            inner,
+            width: width.filter(|&width| width > 0),
+            column: 0,
+        }
+    }
+}
+
+impl<W> Write for LineWrapper<W>
+where
+    W: Write,
+{
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let width = match self.width {
+            Some(width) => width,
+            None => return self.inner.write(buf),
+        };
+        // always consume the whole buffer: some writers upstream, notably
+        // base64's, cope poorly with short writes
+        let mut rest = buf;
+        while !rest.is_empty() {
+            if self.column == width {
+                self.inner.write_all(b"\n")?;
+                self.column = 0;
+            }
+            let n = rest.len().min(width - self.column);
+            self.inner.write_all(&rest[..n])?;
+            self.column += n;
+            rest = &rest[n..];
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(feature = "async")]
+impl<W> tokio::io::AsyncWrite for LineWrapper<W>
+where
+    W: tokio::io::AsyncWrite + Unpin,
+{
+    fn poll_write(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> std::task::Poll<Result<usize>> {
+        use std::task::Poll;
+
+        let this = self.get_mut();
+        let width = match this.width {
+            Some(width) => width,
+            None => return std::pin::Pin::new(&mut this.inner).poll_write(cx, buf),
+        };
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+        if this.column == width {
+            match std::pin::Pin::new(&mut this.inner).poll_write(cx, b"\n") {
+                Poll::Ready(Ok(0)) => {
+                    return Poll::Ready(Err(std::io::ErrorKind::WriteZero.into()))
+                }
+                Poll::Ready(Ok(_)) => this.column = 0,
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        // unlike the blocking writer, this may write less than the whole
+        // buffer: the async base64 encoder copes with short writes
+        let n = buf.len().min(width - this.column);
+        match std::pin::Pin::new(&mut this.inner).poll_write(cx, &buf[..n]) {
+            Poll::Ready(Ok(written)) => {
+                this.column += written;
+                Poll::Ready(Ok(written))
+            }
+            other => other,
+        }
+    }
+
+    fn poll_flush(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<()>> {
+        std::pin::Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<()>> {
+        std::pin::Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    #[test]
+    fn functionality() {
+        let mut out = Vec::new();
+        let mut wrapper = super::LineWrapper::new(Some(4), &mut out);
+        wrapper.write_all(b"0123").unwrap();
+        wrapper.write_all(b"45678").unwrap();
+        wrapper.write_all(b"9ab").unwrap();
+
+        assert_eq!(out, b"0123\n4567\n89ab");
+    }
+
+    #[test]
+    fn no_width() {
+        let mut out = Vec::new();
+        let mut wrapper = super::LineWrapper::new(None, &mut out);
+        wrapper.write_all(b"0123456789").unwrap();
+
+        assert_eq!(out, b"0123456789");
+    }
+}
@@ -0,0 +1,672 @@
+//! Lua table literals, as written by serpent and read by Factorio's Lua API.
+//!
+//! Anything which serializes to json can be written as a Lua table, with
+//! the same field names and nesting as the json: a [`Container`], a
+//! [`Blueprint`], or the entities of a blueprint in the form which
+//! `LuaItemStack::set_blueprint_entities` accepts. Objects become tables
+//! with named fields, arrays become sequences, and keys which are not
+//! identifiers, such as the `"1"` of circuit connections, are bracketed.
+//!
+//! [`from_str`] reads such tables back, as printed by `serpent.line`,
+//! `serpent.block`, or by hand. Comments, a leading `return`, either kind of
+//! string quote, long strings, and explicitly numbered keys are accepted.
+//! Integer keys which do not form a sequence are read as strings, as json
+//! would have them.
+//!
+//! [`Container`]: crate::Container
+//! [`Blueprint`]: crate::objects::Blueprint
+
+use crate::{Error, Result};
+use serde::{
+    de::{
+        self,
+        value::{MapAccessDeserializer, MapDeserializer, SeqDeserializer},
+        DeserializeOwned, IntoDeserializer, MapAccess, SeqAccess, Visitor,
+    },
+    forward_to_deserialize_any, Deserialize, Deserializer, Serialize,
+};
+use std::convert::TryFrom;
+use std::fmt::{self, Write};
+
+/// write the value as a Lua table literal, on one line
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> Result<String> {
+    let mut out = String::new();
+    write_value(&mut out, &Value::from_serialize(value)?, None);
+    Ok(out)
+}
+
+/// write the value as a Lua table literal, with one field per line
+pub fn to_string_pretty<T: Serialize + ?Sized>(value: &T) -> Result<String> {
+    let mut out = String::new();
+    write_value(&mut out, &Value::from_serialize(value)?, Some(0));
+    out.push('\n');
+    Ok(out)
+}
+
+/// read a value from a Lua table literal
+pub fn from_str<T: DeserializeOwned>(lua: &str) -> Result<T> {
+    let value = Parser::new(lua).parse()?;
+    serde_path_to_error::deserialize(value).map_err(|err| Error::LuaValue {
+        path: err.path().to_string(),
+        message: err.into_inner().0,
+    })
+}
+
+/// A Lua value, as far as tables of data go.
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Nil,
+    Boolean(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    /// A sequence, or an empty table.
+    Array(Vec<Value>),
+    /// A table with string keys, in order.
+    Table(Vec<(String, Value)>),
+}
+
+impl Value {
+    fn from_serialize<T: Serialize + ?Sized>(value: &T) -> Result<Value> {
+        // json keeps the order of fields, which `serde_json::Value` does not
+        Ok(serde_json::from_str(&serde_json::to_string(value)?)?)
+    }
+}
+
+// Deserializes from json, for `Value::from_serialize`.
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Value, D::Error> {
+        struct ValueVisitor;
+
+        impl<'de> Visitor<'de> for ValueVisitor {
+            type Value = Value;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("any json value")
+            }
+
+            fn visit_unit<E>(self) -> std::result::Result<Value, E> {
+                Ok(Value::Nil)
+            }
+
+            fn visit_bool<E>(self, v: bool) -> std::result::Result<Value, E> {
+                Ok(Value::Boolean(v))
+            }
+
+            fn visit_i64<E>(self, v: i64) -> std::result::Result<Value, E> {
+                Ok(Value::Integer(v))
+            }
+
+            fn visit_u64<E>(self, v: u64) -> std::result::Result<Value, E> {
+                // Lua integers are signed
+                Ok(i64::try_from(v).map_or(Value::Float(v as f64), Value::Integer))
+            }
+
+            fn visit_f64<E>(self, v: f64) -> std::result::Result<Value, E> {
+                Ok(Value::Float(v))
+            }
+
+            fn visit_str<E>(self, v: &str) -> std::result::Result<Value, E> {
+                Ok(Value::String(v.into()))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> std::result::Result<Value, A::Error> {
+                let mut values = Vec::new();
+                while let Some(value) = seq.next_element()? {
+                    values.push(value);
+                }
+                Ok(Value::Array(values))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(
+                self,
+                mut map: A,
+            ) -> std::result::Result<Value, A::Error> {
+                let mut fields = Vec::new();
+                while let Some(field) = map.next_entry()? {
+                    fields.push(field);
+                }
+                Ok(Value::Table(fields))
+            }
+        }
+
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+const KEYWORDS: &[&str] = &[
+    "and", "break", "do", "else", "elseif", "end", "false", "for", "function", "goto", "if", "in",
+    "local", "nil", "not", "or", "repeat", "return", "then", "true", "until", "while",
+];
+
+fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !KEYWORDS.contains(&key)
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            // a following digit would otherwise extend the escape
+            c if c.is_ascii_control() => write!(out, "\\{:03}", c as u32).expect("infallible"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// write a value, indented to the given depth, or on one line if none
+fn write_value(out: &mut String, value: &Value, indent: Option<usize>) {
+    match value {
+        Value::Nil => out.push_str("nil"),
+        Value::Boolean(v) => write!(out, "{}", v).expect("infallible"),
+        Value::Integer(v) => write!(out, "{}", v).expect("infallible"),
+        Value::Float(v) if v.is_nan() => out.push_str("0/0"),
+        Value::Float(v) if v.is_infinite() => {
+            out.push_str(if *v > 0.0 { "math.huge" } else { "-math.huge" })
+        }
+        // `{:?}` always includes a decimal point or exponent, and is exact
+        Value::Float(v) => write!(out, "{:?}", v).expect("infallible"),
+        Value::String(s) => write_string(out, s),
+        Value::Array(values) => write_table(out, values.iter().map(|v| (None, v)), indent),
+        Value::Table(fields) => write_table(
+            out,
+            fields.iter().map(|(key, v)| (Some(key.as_str()), v)),
+            indent,
+        ),
+    }
+}
+
+fn write_table<'a>(
+    out: &mut String,
+    fields: impl ExactSizeIterator<Item = (Option<&'a str>, &'a Value)>,
+    indent: Option<usize>,
+) {
+    if fields.len() == 0 {
+        out.push_str("{}");
+        return;
+    }
+    out.push('{');
+    let inner = indent.map(|depth| depth + 1);
+    for (n, (key, value)) in fields.enumerate() {
+        if n > 0 {
+            out.push(',');
+        }
+        match inner {
+            Some(depth) => {
+                out.push('\n');
+                out.push_str(&"  ".repeat(depth));
+            }
+            None if n > 0 => out.push(' '),
+            None => {}
+        }
+        match key {
+            Some(key) if is_identifier(key) => write!(out, "{} = ", key).expect("infallible"),
+            Some(key) => {
+                out.push('[');
+                write_string(out, key);
+                out.push_str("] = ");
+            }
+            None => {}
+        }
+        write_value(out, value, inner);
+    }
+    if let Some(depth) = indent {
+        out.push('\n');
+        out.push_str(&"  ".repeat(depth));
+    }
+    out.push('}');
+}
+
+/// How deeply tables may nest, as in serde_json: deep enough for any
+/// blueprint, and shallow enough not to overflow the stack.
+const MAX_DEPTH: usize = 128;
+
+/// Reads a single Lua value.
+struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+    /// The number of tables which enclose the position.
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Parser<'a> {
+        Parser {
+            input,
+            position: 0,
+            depth: 0,
+        }
+    }
+
+    fn parse(mut self) -> Result<Value> {
+        self.skip_whitespace()?;
+        if self.eat_word("return") {
+            self.skip_whitespace()?;
+        }
+        let value = self.value()?;
+        self.skip_whitespace()?;
+        self.eat(";");
+        self.skip_whitespace()?;
+        if self.position < self.input.len() {
+            return Err(self.error("unexpected input after value"));
+        }
+        Ok(value)
+    }
+
+    fn error(&self, message: impl Into<String>) -> Error {
+        Error::LuaSyntax {
+            line: self.input[..self.position].matches('\n').count() + 1,
+            message: message.into(),
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.position..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        if self.rest().starts_with(token) {
+            self.position += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<()> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{}`", token)))
+        }
+    }
+
+    /// consume the given word, if it is not merely the start of a longer one
+    fn eat_word(&mut self, word: &str) -> bool {
+        let rest = self.rest();
+        let matches = rest.starts_with(word)
+            && rest[word.len()..]
+                .chars()
+                .next()
+                .is_none_or(|c| !(c.is_ascii_alphanumeric() || c == '_'));
+        matches && self.eat(word)
+    }
+
+    fn skip_whitespace(&mut self) -> Result<()> {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.position += rest.len() - trimmed.len();
+            if !self.eat("--") {
+                return Ok(());
+            }
+            if let Some(level) = self.long_bracket_level() {
+                self.long_bracket(level)?;
+            } else {
+                self.position += self.rest().find('\n').unwrap_or(self.rest().len());
+            }
+        }
+    }
+
+    /// the level of the long bracket opening here, such as 2 for `[==[`
+    fn long_bracket_level(&self) -> Option<usize> {
+        let rest = self.rest().strip_prefix('[')?;
+        let level = rest.len() - rest.trim_start_matches('=').len();
+        rest[level..].starts_with('[').then_some(level)
+    }
+
+    /// read the contents of the long bracket of the given level which
+    /// opens here
+    fn long_bracket(&mut self, level: usize) -> Result<&'a str> {
+        self.position += level + 2;
+        let close = format!("]{}]", "=".repeat(level));
+        let length = self
+            .rest()
+            .find(&close)
+            .ok_or_else(|| self.error("unterminated long bracket"))?;
+        let contents = &self.rest()[..length];
+        self.position += length + close.len();
+        // a newline straight after the opening bracket is skipped
+        Ok(contents
+            .strip_prefix("\r\n")
+            .or_else(|| contents.strip_prefix('\n'))
+            .unwrap_or(contents))
+    }
+
+    fn value(&mut self) -> Result<Value> {
+        match self.peek() {
+            Some('{') => self.table(),
+            Some('"') | Some('\'') => self.string().map(Value::String),
+            Some('[') => match self.long_bracket_level() {
+                Some(level) => self.long_bracket(level).map(|s| Value::String(s.into())),
+                None => Err(self.error("expected a value")),
+            },
+            Some(c) if c.is_ascii_digit() || c == '-' || c == '.' => self.number(),
+            _ if self.eat_word("nil") => Ok(Value::Nil),
+            _ if self.eat_word("true") => Ok(Value::Boolean(true)),
+            _ if self.eat_word("false") => Ok(Value::Boolean(false)),
+            _ if self.eat("math.huge") => Ok(Value::Float(f64::INFINITY)),
+            _ => Err(self.error("expected a value")),
+        }
+    }
+
+    fn number(&mut self) -> Result<Value> {
+        let negative = self.eat("-");
+        self.skip_whitespace()?;
+        if self.eat("math.huge") {
+            let infinity = if negative {
+                -f64::INFINITY
+            } else {
+                f64::INFINITY
+            };
+            return Ok(Value::Float(infinity));
+        }
+        let start = self.position;
+        let hex = self.eat("0x") || self.eat("0X");
+        let length = self
+            .rest()
+            .find(|c: char| {
+                !(c.is_ascii_hexdigit() || c == '.' || (!hex && matches!(c, 'e' | 'E' | '+' | '-')))
+            })
+            .unwrap_or(self.rest().len());
+        self.position += length;
+        let literal = &self.input[start..self.position];
+        let number = if hex {
+            i64::from_str_radix(&literal[2..], 16)
+                .map(Value::Integer)
+                .map_err(|_| self.error(format!("invalid number `{}`", literal)))?
+        } else if let Ok(v) = literal.parse::<i64>() {
+            Value::Integer(v)
+        } else {
+            literal
+                .parse::<f64>()
+                .map(Value::Float)
+                .map_err(|_| self.error(format!("invalid number `{}`", literal)))?
+        };
+        self.skip_whitespace()?;
+        // serpent writes nan as `0/0`
+        if self.eat("/") {
+            self.skip_whitespace()?;
+            let divisor = self.number()?;
+            return match (number, divisor) {
+                (Value::Integer(0), Value::Integer(0)) => Ok(Value::Float(f64::NAN)),
+                _ => Err(self.error("expected `0/0`")),
+            };
+        }
+        Ok(match number {
+            Value::Integer(v) if negative => Value::Integer(-v),
+            Value::Float(v) if negative => Value::Float(-v),
+            number => number,
+        })
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let quote = self.peek().expect("called at a quote");
+        self.position += 1;
+        // escapes produce bytes, which together must be utf8
+        let mut out = Vec::new();
+        loop {
+            let c = self
+                .peek()
+                .filter(|&c| c != '\n')
+                .ok_or_else(|| self.error("unterminated string"))?;
+            self.position += c.len_utf8();
+            match c {
+                c if c == quote => {
+                    return String::from_utf8(out).map_err(|_| self.error("string is not utf8"))
+                }
+                '\\' => self.escape(&mut out)?,
+                c => out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+            }
+        }
+    }
+
+    /// read the escape sequence following a backslash
+    fn escape(&mut self, out: &mut Vec<u8>) -> Result<()> {
+        let c = self
+            .peek()
+            .ok_or_else(|| self.error("unterminated string"))?;
+        self.position += c.len_utf8();
+        match c {
+            'n' | '\n' => out.push(b'\n'),
+            'r' => out.push(b'\r'),
+            't' => out.push(b'\t'),
+            'a' => out.push(0x07),
+            'b' => out.push(0x08),
+            'f' => out.push(0x0c),
+            'v' => out.push(0x0b),
+            'z' => {
+                let rest = self.rest();
+                self.position += rest.len() - rest.trim_start().len();
+            }
+            'x' => {
+                let byte = self
+                    .rest()
+                    .get(..2)
+                    .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                    .ok_or_else(|| self.error("bad `\\x` escape"))?;
+                self.position += 2;
+                out.push(byte);
+            }
+            'u' => {
+                let rest = self.rest();
+                let end = rest.find('}').unwrap_or(0);
+                let c = rest
+                    .strip_prefix('{')
+                    .and_then(|rest| u32::from_str_radix(&rest[..end.max(1) - 1], 16).ok())
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| self.error("bad `\\u` escape"))?;
+                self.position += end + 1;
+                out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+            }
+            '0'..='9' => {
+                let start = self.position - 1;
+                let digits = 1 + self
+                    .rest()
+                    .bytes()
+                    .take(2)
+                    .take_while(u8::is_ascii_digit)
+                    .count();
+                let byte = self.input[start..start + digits]
+                    .parse()
+                    .map_err(|_| self.error("bad decimal escape"))?;
+                self.position = start + digits;
+                out.push(byte);
+            }
+            c => out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+        }
+        Ok(())
+    }
+
+    fn table(&mut self) -> Result<Value> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error(format!("tables are nested more than {} deep", MAX_DEPTH)));
+        }
+        self.depth += 1;
+        let table = self.table_contents();
+        self.depth -= 1;
+        table
+    }
+
+    fn table_contents(&mut self) -> Result<Value> {
+        self.expect("{")?;
+        let mut positional = Vec::new();
+        let mut indexed = Vec::new();
+        let mut named = Vec::new();
+        loop {
+            self.skip_whitespace()?;
+            if self.eat("}") {
+                break;
+            }
+            if self.eat("[") {
+                self.skip_whitespace()?;
+                let key = self.value()?;
+                self.skip_whitespace()?;
+                self.expect("]")?;
+                self.skip_whitespace()?;
+                self.expect("=")?;
+                self.skip_whitespace()?;
+                let value = self.value()?;
+                match key {
+                    Value::String(key) => named.push((key, value)),
+                    Value::Integer(index) => indexed.push((index, value)),
+                    _ => return Err(self.error("table keys must be strings or integers")),
+                }
+            } else {
+                let rest = self.rest();
+                let length = rest
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                let name = &rest[..length];
+                let after = rest[length..].trim_start();
+                if is_identifier(name) && after.starts_with('=') && !after.starts_with("==") {
+                    self.position += rest.len() - after.len() + 1;
+                    self.skip_whitespace()?;
+                    named.push((name.to_string(), self.value()?));
+                } else {
+                    positional.push(self.value()?);
+                }
+            }
+            self.skip_whitespace()?;
+            if !(self.eat(",") || self.eat(";")) {
+                self.skip_whitespace()?;
+                self.expect("}")?;
+                break;
+            }
+        }
+        // nil values are the same as absent ones
+        named.retain(|(_, value)| *value != Value::Nil);
+        indexed.retain(|(_, value)| *value != Value::Nil);
+        // `{[1] = a, [2] = b}` is just as much a sequence as `{a, b}`
+        let is_sequence = indexed
+            .iter()
+            .enumerate()
+            .all(|(n, (index, _))| *index as usize == positional.len() + n + 1);
+        if named.is_empty() && is_sequence {
+            positional.extend(indexed.into_iter().map(|(_, value)| value));
+            return Ok(Value::Array(positional));
+        }
+        if !positional.is_empty() {
+            return Err(self.error("tables may not mix sequences and named fields"));
+        }
+        // other integer keys are as good as strings, as they are in json
+        let indexed = indexed
+            .into_iter()
+            .map(|(index, value)| (index.to_string(), value));
+        Ok(Value::Table(indexed.chain(named).collect()))
+    }
+}
+
+/// An error found while deserializing a parsed value.
+#[derive(Debug)]
+struct ValueError(String);
+
+impl fmt::Display for ValueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for ValueError {}
+
+impl de::Error for ValueError {
+    fn custom<T: fmt::Display>(msg: T) -> ValueError {
+        ValueError(msg.to_string())
+    }
+}
+
+impl<'de> IntoDeserializer<'de, ValueError> for Value {
+    type Deserializer = Value;
+
+    fn into_deserializer(self) -> Value {
+        self
+    }
+}
+
+impl<'de> Deserializer<'de> for Value {
+    type Error = ValueError;
+
+    fn deserialize_any<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> std::result::Result<V::Value, ValueError> {
+        match self {
+            Value::Nil => visitor.visit_unit(),
+            Value::Boolean(v) => visitor.visit_bool(v),
+            Value::Integer(v) => visitor.visit_i64(v),
+            // Lua 5.3 may write whole floats as `1.0`
+            Value::Float(v) if v.fract() == 0.0 && v.abs() < (1_u64 << 53) as f64 => {
+                visitor.visit_i64(v as i64)
+            }
+            Value::Float(v) => visitor.visit_f64(v),
+            Value::String(v) => visitor.visit_string(v),
+            // an empty table is as likely to be an object as an array
+            Value::Array(values) if values.is_empty() => {
+                visitor.visit_map(MapDeserializer::new(std::iter::empty::<(String, Value)>()))
+            }
+            Value::Array(values) => visitor.visit_seq(SeqDeserializer::new(values.into_iter())),
+            Value::Table(fields) => visitor.visit_map(MapDeserializer::new(fields.into_iter())),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> std::result::Result<V::Value, ValueError> {
+        match self {
+            Value::Nil => visitor.visit_none(),
+            value => visitor.visit_some(value),
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> std::result::Result<V::Value, ValueError> {
+        match self {
+            Value::Array(values) => visitor.visit_seq(SeqDeserializer::new(values.into_iter())),
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> std::result::Result<V::Value, ValueError> {
+        match self {
+            Value::String(variant) => visitor.visit_enum(variant.into_deserializer()),
+            Value::Table(fields) if fields.len() == 1 => visitor.visit_enum(
+                MapAccessDeserializer::new(MapDeserializer::new(fields.into_iter())),
+            ),
+            _ => Err(de::Error::custom(
+                "expected a string, or a table with a single field",
+            )),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct newtype_struct tuple tuple_struct
+        map struct identifier ignored_any
+    }
+}
@@ -9,8 +9,11 @@ fn main() {
 #[cfg(feature = "codec-cli")]
 mod m {
     pub use anyhow::Result;
-    pub use factorio_blueprint::BlueprintCodec;
-    pub use std::io::{copy, stdin, stdout, BufReader, Cursor, Read};
+    pub use factorio_blueprint::{
+        encode_options::{EncodeOptions, MAX_COMPRESSION_LEVEL},
+        lenient, BlueprintCodec, Container, Format,
+    };
+    pub use std::io::{copy, stdin, stdout, BufReader, Cursor, Read, Write};
     pub use std::path::PathBuf;
     pub use structopt::StructOpt;
 
@@ -36,6 +39,59 @@ mod m {
         }
     }
 
+    #[derive(Debug, StructOpt)]
+    pub struct EncodeOpts {
+        /// zlib compression level, from 0 (store only) to 9 (best)
+        #[structopt(short, long, default_value = "9", parse(try_from_str = parse_level))]
+        level: u32,
+        /// use fast compression, for bulk re-encoding; overrides --level
+        #[structopt(long)]
+        fast: bool,
+        /// version byte which prefixes the blueprint string
+        #[structopt(long, default_value = "0", parse(try_from_str = parse_version_byte))]
+        version_byte: char,
+        /// insert a newline after every WIDTH characters of output
+        #[structopt(short, long, name = "WIDTH")]
+        wrap: Option<usize>,
+        /// sort entities, tiles, icons, and book pages, so that equivalent
+        /// blueprints encode identically
+        #[structopt(long)]
+        canonical: bool,
+    }
+
+    fn parse_level(s: &str) -> std::result::Result<u32, String> {
+        match s.parse() {
+            Ok(level) if level <= MAX_COMPRESSION_LEVEL => Ok(level),
+            _ => Err(format!(
+                "must be a number from 0 to {}",
+                MAX_COMPRESSION_LEVEL
+            )),
+        }
+    }
+
+    fn parse_version_byte(s: &str) -> std::result::Result<char, String> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(version), None) if version.is_ascii() => Ok(version),
+            _ => Err("must be a single ascii character".into()),
+        }
+    }
+
+    impl EncodeOpts {
+        pub fn options(&self) -> EncodeOptions {
+            EncodeOptions {
+                compression_level: if self.fast {
+                    EncodeOptions::fast().compression_level
+                } else {
+                    self.level
+                },
+                version: self.version_byte,
+                line_width: self.wrap,
+                canonical: self.canonical,
+            }
+        }
+    }
+
     #[derive(Debug, StructOpt)]
     #[structopt(about = "Convert between JSON and Factorio blueprint strings")]
     pub enum Opt {
@@ -43,12 +99,61 @@ mod m {
         Encode {
             #[structopt(flatten)]
             codec_opts: CodecOpts,
+            #[structopt(flatten)]
+            encode_opts: EncodeOpts,
         },
         #[structopt(about = "decode a blueprint string to JSON")]
         Decode {
             #[structopt(flatten)]
             codec_opts: CodecOpts,
+            /// repair strings mangled by quotes, code fences, url-safe base64, etc.
+            #[structopt(long)]
+            lenient: bool,
+        },
+        #[structopt(about = "print each page of a blueprint book, nested or not, as JSON")]
+        Pages {
+            #[structopt(flatten)]
+            codec_opts: CodecOpts,
+        },
+        #[structopt(about = "find every blueprint string in some text, and print each as JSON")]
+        Extract {
+            #[structopt(flatten)]
+            codec_opts: CodecOpts,
+        },
+        #[structopt(about = "split a blueprint book into books whose strings are each at \
+                             most a given size, printing one per line")]
+        Split {
+            #[structopt(flatten)]
+            codec_opts: CodecOpts,
+            #[structopt(flatten)]
+            encode_opts: EncodeOpts,
+            /// the longest each blueprint string may be, in bytes
+            #[structopt(long)]
+            max_bytes: usize,
+        },
+        #[structopt(about = "re-encode a blueprint string as the shortest string which \
+                             builds the same thing")]
+        Optimize {
+            #[structopt(flatten)]
+            codec_opts: CodecOpts,
         },
+        #[structopt(
+            about = "convert between blueprint strings, JSON, Lua, YAML, TOML, RON, \
+                             MessagePack and CBOR"
+        )]
+        Convert {
+            #[structopt(flatten)]
+            codec_opts: CodecOpts,
+            /// format of the input
+            #[structopt(long, default_value = "blueprint")]
+            from: Format,
+            /// format of the output
+            #[structopt(long, default_value = "json")]
+            to: Format,
+        },
+        #[cfg(feature = "schemars")]
+        #[structopt(about = "print the JSON Schema of the JSON which the codec reads")]
+        Schema,
     }
 }
 
@@ -59,21 +164,120 @@ use m::*;
 fn main() -> Result<()> {
     let opt = Opt::from_args();
     match opt {
-        Opt::Encode { codec_opts } => {
+        Opt::Encode {
+            codec_opts,
+            encode_opts,
+        } => {
             let mut reader = codec_opts.reader()?;
             let writer = stdout();
             let writer = writer.lock();
-            BlueprintCodec::encode_writer(writer, |mut writer| {
-                copy(&mut reader, &mut writer).map(|_| ())
-            })?
+            let options = encode_opts.options();
+            if options.canonical {
+                // canonicalizing requires understanding the json
+                let container: Container = serde_json::from_reader(reader)?;
+                BlueprintCodec::encode_with(writer, &container, &options)?
+            } else {
+                BlueprintCodec::encode_writer_with(writer, &options, |mut writer| {
+                    copy(&mut reader, &mut writer).map(|_| ())
+                })?
+            }
         }
-        Opt::Decode { codec_opts } => {
-            BlueprintCodec::decode_reader(codec_opts.reader()?, |mut reader| {
+        Opt::Decode {
+            codec_opts,
+            lenient,
+        } => {
+            let mut reader = codec_opts.reader()?;
+            if lenient {
+                let mut blueprint = String::new();
+                reader.read_to_string(&mut blueprint)?;
+                let (blueprint, repairs) = lenient::normalize(&blueprint);
+                for repair in repairs {
+                    eprintln!("repair: {}", repair);
+                }
+                reader = Box::new(Cursor::new(blueprint.into_bytes()));
+            }
+            BlueprintCodec::decode_reader(reader, |mut reader| {
                 let writer = stdout();
                 let mut writer = writer.lock();
                 copy(&mut reader, &mut writer).map(|_| ())
             })?
         }
+        Opt::Pages { codec_opts } => {
+            let writer = stdout();
+            let mut writer = writer.lock();
+            BlueprintCodec::decode_pages(codec_opts.reader()?, |pages| {
+                for page in pages {
+                    serde_json::to_writer(&mut writer, &page?)?;
+                    writeln!(writer)?;
+                }
+                Ok(())
+            })?;
+            return Ok(());
+        }
+        Opt::Extract { codec_opts } => {
+            let mut text = String::new();
+            codec_opts.reader()?.read_to_string(&mut text)?;
+            let writer = stdout();
+            let mut writer = writer.lock();
+            for (range, result) in BlueprintCodec::find_all(&text) {
+                match result {
+                    Ok(container) => {
+                        serde_json::to_writer(&mut writer, &container)?;
+                        writeln!(writer)?;
+                    }
+                    Err(err) => eprintln!("skipping {:?}: {}", range, err),
+                }
+            }
+            return Ok(());
+        }
+        Opt::Split {
+            codec_opts,
+            encode_opts,
+            max_bytes,
+        } => {
+            let book = match BlueprintCodec::decode(codec_opts.reader()?)? {
+                Container::BlueprintBook(book) => book,
+                _ => anyhow::bail!("only blueprint books can be split"),
+            };
+            let strings = BlueprintCodec::encode_split(&book, max_bytes, &encode_opts.options())?;
+            let writer = stdout();
+            let mut writer = writer.lock();
+            for string in strings {
+                writeln!(writer, "{}", string)?;
+            }
+            return Ok(());
+        }
+        Opt::Optimize { codec_opts } => {
+            let container = BlueprintCodec::decode(codec_opts.reader()?)?;
+            let optimized = BlueprintCodec::encode_minimal(&container)?;
+            eprintln!(
+                "saved {} bytes: {} -> {}",
+                optimized.bytes_saved(),
+                optimized.original_len,
+                optimized.string.len()
+            );
+            print!("{}", optimized.string);
+        }
+        Opt::Convert {
+            codec_opts,
+            from,
+            to,
+        } => {
+            let mut input = Vec::new();
+            codec_opts.reader()?.read_to_end(&mut input)?;
+            let output = Container::from_format(&input, from)?.to_format(to)?;
+            let writer = stdout();
+            let mut writer = writer.lock();
+            writer.write_all(&output)?;
+            if to.is_binary() || output.ends_with(b"\n") {
+                return Ok(());
+            }
+        }
+        #[cfg(feature = "schemars")]
+        Opt::Schema => {
+            let schema = factorio_blueprint::objects::schema();
+            print!("{}", serde_json::to_string_pretty(&schema)?);
+        }
     }
     println!();
     Ok(())
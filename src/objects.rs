@@ -1,20 +1,34 @@
+pub use crate::prototype::Prototype;
 use crate::Container;
 use noisy_float::types::R64;
-use serde::{Deserialize, Serialize, Serializer};
+#[cfg(feature = "schemars")]
+use schemars::{JsonSchema, JsonSchema_repr};
+use serde::{
+    de::{self, MapAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
 use serde_repr::{Deserialize_repr, Serialize_repr};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::fmt;
 
 const DEFAULT_VERSION: u64 = 77310525440;
 
-pub type Prototype = String;
 pub type EntityNumber = OneBasedIndex;
 pub type ItemStackIndex = u16;
 pub type ItemCountType = u32;
 pub type GraphicsVariation = u8;
 pub type OneBasedIndex = std::num::NonZeroUsize;
 
+/// the JSON Schema of a [`Container`]'s json, as read by
+/// [`BlueprintCodec`](crate::BlueprintCodec)
+#[cfg(feature = "schemars")]
+pub fn schema() -> schemars::Schema {
+    schemars::schema_for!(Container)
+}
+
 /// https://wiki.factorio.com/Blueprint_string_format#Blueprint_book_object
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[serde(default)]
 pub struct BlueprintBook {
     pub item: String,
@@ -47,7 +61,29 @@ impl Default for BlueprintBook {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+impl BlueprintBook {
+    /// Put this book into canonical form: pages and icons are sorted by
+    /// index, and every page is canonicalized in turn.
+    pub fn canonicalize(&mut self) {
+        self.blueprints.sort_by_key(|page| page.index);
+        self.icons.sort_by_key(|icon| icon.index);
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::prelude::*;
+            self.blueprints
+                .par_iter_mut()
+                .for_each(|page| page.item.canonicalize());
+        }
+        #[cfg(not(feature = "rayon"))]
+        for page in &mut self.blueprints {
+            page.item.canonicalize();
+        }
+    }
+}
+
+/// A page of a blueprint book: its position in the book, and what it holds.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub struct BlueprintBookBlueprintValue {
     pub index: usize,
@@ -55,8 +91,131 @@ pub struct BlueprintBookBlueprintValue {
     pub item: Container,
 }
 
+// Deserialization is implemented by hand instead of with `#[serde(flatten)]`:
+// flattening buffers the whole item, which loses track of where in the item
+// any error occurred.
+impl<'de> Deserialize<'de> for BlueprintBookBlueprintValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (index, item) = deserialize_page(deserializer)?;
+        Ok(BlueprintBookBlueprintValue { index, item })
+    }
+}
+
+/// The key under which a blueprint book page stores its item.
+pub(crate) enum PageField {
+    Index,
+    BlueprintBook,
+    Blueprint,
+    DeconstructionPlanner,
+    UpgradePlanner,
+    Other,
+}
+
+// Keys are read as strings rather than as identifiers, as some formats, such
+// as RON, only accept bare identifiers for the latter.
+impl<'de> Deserialize<'de> for PageField {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct PageFieldVisitor;
+
+        impl<'de> Visitor<'de> for PageFieldVisitor {
+            type Value = PageField;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a field name")
+            }
+
+            fn visit_str<E: de::Error>(self, field: &str) -> Result<PageField, E> {
+                Ok(match field {
+                    "index" => PageField::Index,
+                    "blueprint_book" => PageField::BlueprintBook,
+                    "blueprint" => PageField::Blueprint,
+                    "deconstruction_planner" => PageField::DeconstructionPlanner,
+                    "upgrade_planner" => PageField::UpgradePlanner,
+                    _ => PageField::Other,
+                })
+            }
+        }
+
+        deserializer.deserialize_str(PageFieldVisitor)
+    }
+}
+
+/// Types which can be read from the item of a blueprint book page.
+pub(crate) trait PageItem<'de>: Sized {
+    /// read the value of the item stored under `field`, which is never
+    /// `Index` or `Other`
+    fn next_item<A: MapAccess<'de>>(field: PageField, map: &mut A) -> Result<Self, A::Error>;
+}
+
+impl<'de> PageItem<'de> for Container {
+    fn next_item<A: MapAccess<'de>>(field: PageField, map: &mut A) -> Result<Self, A::Error> {
+        Ok(match field {
+            PageField::BlueprintBook => Container::BlueprintBook(map.next_value()?),
+            PageField::Blueprint => Container::Blueprint(map.next_value()?),
+            PageField::DeconstructionPlanner => Container::DeconstructionPlanner(map.next_value()?),
+            PageField::UpgradePlanner => Container::UpgradePlanner(map.next_value()?),
+            PageField::Index | PageField::Other => unreachable!("not an item field"),
+        })
+    }
+}
+
+/// deserialize a blueprint book page into its index and its item
+pub(crate) fn deserialize_page<'de, D, T>(deserializer: D) -> Result<(usize, T), D::Error>
+where
+    D: Deserializer<'de>,
+    T: PageItem<'de>,
+{
+    struct PageVisitor<T>(std::marker::PhantomData<T>);
+
+    impl<'de, T: PageItem<'de>> Visitor<'de> for PageVisitor<T> {
+        type Value = (usize, T);
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a blueprint book page")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut index = None;
+            let mut item = None;
+            while let Some(field) = map.next_key()? {
+                match field {
+                    PageField::Index => {
+                        if index.is_some() {
+                            return Err(de::Error::duplicate_field("index"));
+                        }
+                        index = Some(map.next_value()?);
+                    }
+                    PageField::Other => {
+                        map.next_value::<de::IgnoredAny>()?;
+                    }
+                    field => {
+                        if item.is_some() {
+                            return Err(de::Error::custom(
+                                "blueprint book page contains more than one item",
+                            ));
+                        }
+                        item = Some(T::next_item(field, &mut map)?);
+                    }
+                }
+            }
+            Ok((
+                index.ok_or_else(|| de::Error::missing_field("index"))?,
+                item.ok_or_else(|| {
+                    de::Error::custom(
+                        "blueprint book page contains none of `blueprint_book`, \
+                         `blueprint`, `deconstruction_planner` or `upgrade_planner`",
+                    )
+                })?,
+            ))
+        }
+    }
+
+    deserializer.deserialize_map(PageVisitor(std::marker::PhantomData))
+}
+
 /// https://wiki.factorio.com/Blueprint_string_format#Blueprint_object
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[serde(default, rename_all = "kebab-case")]
 pub struct Blueprint {
     pub item: String,
@@ -103,7 +262,26 @@ impl Default for Blueprint {
     }
 }
 
+impl Blueprint {
+    /// Put this blueprint into canonical form: entities are sorted by entity
+    /// number, tiles by position, icons by index, and verbose item requests
+    /// by item. None of these orders have any meaning in game.
+    pub fn canonicalize(&mut self) {
+        self.entities.sort_by_key(|entity| entity.entity_number);
+        self.tiles.sort_by(|a, b| {
+            (a.position.y, a.position.x, &a.name).cmp(&(b.position.y, b.position.x, &b.name))
+        });
+        self.icons.sort_by_key(|icon| icon.index);
+        for entity in &mut self.entities {
+            if let Some(ItemRequest::Verbose(requests)) = &mut entity.items {
+                requests.sort_by(|a, b| a.item.cmp(&b.item));
+            }
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[serde(default)]
 pub struct DeconstructionPlanner {
     pub item: String,
@@ -125,6 +303,7 @@ impl Default for DeconstructionPlanner {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct DeconstructionSettings {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
@@ -143,12 +322,14 @@ pub struct DeconstructionSettings {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct DeconstructionFilter {
     index: u32,
     name: String,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[serde(default)]
 pub struct UpgradePlanner {
     pub item: String,
@@ -170,6 +351,7 @@ impl Default for UpgradePlanner {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct UpgradePlannerSettings {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mappers: Option<Vec<Mapper>>,
@@ -180,6 +362,7 @@ pub struct UpgradePlannerSettings {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct Mapper {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub from: Option<SimpleEntity>,
@@ -189,6 +372,7 @@ pub struct Mapper {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct SimpleEntity {
     #[serde(rename = "type")]
     pub type_: String,
@@ -197,6 +381,7 @@ pub struct SimpleEntity {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize_repr, Serialize_repr)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema_repr))]
 #[repr(u32)]
 pub enum DeconstructionEntityFilterMode {
     // Note: Factorio produces and requires ints
@@ -205,6 +390,7 @@ pub enum DeconstructionEntityFilterMode {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize_repr, Serialize_repr)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema_repr))]
 #[repr(u32)]
 pub enum TileSelectionMode {
     // Note: Factorio produces and requires ints
@@ -216,6 +402,7 @@ pub enum TileSelectionMode {
 
 /// https://wiki.factorio.com/Blueprint_string_format#Icon_object
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct Icon {
     pub index: OneBasedIndex,
     pub signal: SignalID,
@@ -223,6 +410,7 @@ pub struct Icon {
 
 /// https://wiki.factorio.com/Blueprint_string_format#SignalID_object
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct SignalID {
     pub name: Prototype,
     #[serde(rename = "type")]
@@ -230,6 +418,7 @@ pub struct SignalID {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum SignalIDType {
     Item,
@@ -238,74 +427,335 @@ pub enum SignalIDType {
 }
 
 /// https://wiki.factorio.com/Blueprint_string_format#Entity_object
-#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+///
+/// Settings which only a few kinds of entity have are kept in
+/// [`EntityExtra`], which is only allocated for entities which have any.
+/// This keeps the entities of large blueprints small in memory; it does not
+/// affect their json, nor their equality: an entity whose `extra` is
+/// allocated but empty equals one whose `extra` is `None`.
+#[derive(Debug, Eq, Clone, Deserialize)]
+#[serde(from = "EntityRepr")]
 pub struct Entity {
     pub entity_number: EntityNumber,
     pub name: Prototype,
     pub position: Position,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub direction: Option<Direction>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub orientation: Option<R64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub connections: Option<EntityConnections>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub control_behavior: Option<ControlBehavior>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Boxed, as it is large and most entities have none.
+    pub control_behavior: Option<Box<ControlBehavior>>,
     pub items: Option<ItemRequest>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub recipe: Option<Prototype>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub bar: Option<ItemStackIndex>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub inventory: Option<Inventory>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub infinity_settings: Option<InfinitySettings>,
-    #[serde(rename = "type")]
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub type_: Option<EntityType>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub input_priority: Option<EntityPriority>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub output_priority: Option<EntityPriority>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub filter: Option<Prototype>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub filters: Option<Vec<ItemFilter>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub filter_mode: Option<EntityFilterMode>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub override_stack_size: Option<u8>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub drop_position: Option<Position>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub pickup_position: Option<Position>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variation: Option<GraphicsVariation>,
+    pub color: Option<Color>,
+    pub neighbours: Option<Vec<EntityNumber>>,
+    pub extra: Option<Box<EntityExtra>>,
+}
+
+/// The rarely used settings of an [`Entity`].
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct EntityExtra {
+    /// Inventory filters and limit, for cargo wagons.
+    pub inventory: Option<Inventory>,
+    /// For infinity chests and pipes.
+    pub infinity_settings: Option<InfinitySettings>,
+    /// For filter inserters with a single filter, and loaders.
+    pub filter: Option<Prototype>,
+    /// For filter inserters.
+    pub filters: Option<Vec<ItemFilter>>,
+    /// For filter inserters.
+    pub filter_mode: Option<EntityFilterMode>,
+    /// For requester and buffer chests.
     pub request_filters: Option<Vec<LogisticFilter>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// For requester chests.
     pub request_from_buffers: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// For programmable speakers.
     pub parameters: Option<SpeakerParameter>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// For programmable speakers.
     pub alert_parameters: Option<SpeakerAlertParameter>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// For rocket silos.
     pub auto_launch: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub variation: Option<GraphicsVariation>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub color: Option<Color>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// For train stops.
     pub station: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// For power switches.
     pub switch_state: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// For train stops.
     pub manual_trains_limit: Option<u32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub neighbours: Option<Vec<EntityNumber>>,
+}
+
+static NO_EXTRA: EntityExtra = EntityExtra {
+    inventory: None,
+    infinity_settings: None,
+    filter: None,
+    filters: None,
+    filter_mode: None,
+    request_filters: None,
+    request_from_buffers: None,
+    parameters: None,
+    alert_parameters: None,
+    auto_launch: None,
+    station: None,
+    switch_state: None,
+    manual_trains_limit: None,
+};
+
+impl EntityExtra {
+    /// true when none of these settings are set
+    pub fn is_empty(&self) -> bool {
+        *self == NO_EXTRA
+    }
+}
+
+impl Entity {
+    /// the rarely used settings of this entity, if any are set
+    pub fn extra(&self) -> Option<&EntityExtra> {
+        self.extra.as_deref().filter(|extra| !extra.is_empty())
+    }
+
+    /// the rarely used settings of this entity, allocating them if need be
+    pub fn extra_mut(&mut self) -> &mut EntityExtra {
+        self.extra.get_or_insert_with(Default::default)
+    }
+}
+
+impl PartialEq for Entity {
+    fn eq(&self, other: &Entity) -> bool {
+        // destructured, so that a new field cannot be forgotten here
+        let Entity {
+            entity_number,
+            name,
+            position,
+            direction,
+            orientation,
+            connections,
+            control_behavior,
+            items,
+            recipe,
+            bar,
+            type_,
+            input_priority,
+            output_priority,
+            override_stack_size,
+            drop_position,
+            pickup_position,
+            variation,
+            color,
+            neighbours,
+            extra: _,
+        } = self;
+        *entity_number == other.entity_number
+            && *name == other.name
+            && *position == other.position
+            && *direction == other.direction
+            && *orientation == other.orientation
+            && *connections == other.connections
+            && *control_behavior == other.control_behavior
+            && *items == other.items
+            && *recipe == other.recipe
+            && *bar == other.bar
+            && *type_ == other.type_
+            && *input_priority == other.input_priority
+            && *output_priority == other.output_priority
+            && *override_stack_size == other.override_stack_size
+            && *drop_position == other.drop_position
+            && *pickup_position == other.pickup_position
+            && *variation == other.variation
+            && *color == other.color
+            && *neighbours == other.neighbours
+            && self.extra() == other.extra()
+    }
+}
+
+/// The json representation of an [`Entity`], from which it is deserialized.
+#[derive(Deserialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[cfg_attr(
+    feature = "schemars",
+    schemars(description = "https://wiki.factorio.com/Blueprint_string_format#Entity_object")
+)]
+struct EntityRepr {
+    entity_number: EntityNumber,
+    name: Prototype,
+    position: Position,
+    direction: Option<Direction>,
+    #[serde(default, deserialize_with = "deserialize_optional_r64")]
+    #[cfg_attr(feature = "schemars", schemars(with = "Option<f64>"))]
+    orientation: Option<R64>,
+    connections: Option<EntityConnections>,
+    control_behavior: Option<Box<ControlBehavior>>,
+    items: Option<ItemRequest>,
+    recipe: Option<Prototype>,
+    bar: Option<ItemStackIndex>,
+    inventory: Option<Inventory>,
+    infinity_settings: Option<InfinitySettings>,
+    #[serde(rename = "type")]
+    type_: Option<EntityType>,
+    input_priority: Option<EntityPriority>,
+    output_priority: Option<EntityPriority>,
+    filter: Option<Prototype>,
+    filters: Option<Vec<ItemFilter>>,
+    filter_mode: Option<EntityFilterMode>,
+    override_stack_size: Option<u8>,
+    drop_position: Option<Position>,
+    pickup_position: Option<Position>,
+    request_filters: Option<Vec<LogisticFilter>>,
+    request_from_buffers: Option<bool>,
+    parameters: Option<SpeakerParameter>,
+    alert_parameters: Option<SpeakerAlertParameter>,
+    auto_launch: Option<bool>,
+    variation: Option<GraphicsVariation>,
+    color: Option<Color>,
+    station: Option<String>,
+    switch_state: Option<bool>,
+    manual_trains_limit: Option<u32>,
+    neighbours: Option<Vec<EntityNumber>>,
+}
+
+/// An entity's schema is that of the json from which it is read.
+#[cfg(feature = "schemars")]
+impl JsonSchema for Entity {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "Entity".into()
+    }
+
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        EntityRepr::json_schema(generator)
+    }
+}
+
+impl From<EntityRepr> for Entity {
+    fn from(repr: EntityRepr) -> Entity {
+        let extra = EntityExtra {
+            inventory: repr.inventory,
+            infinity_settings: repr.infinity_settings,
+            filter: repr.filter,
+            filters: repr.filters,
+            filter_mode: repr.filter_mode,
+            request_filters: repr.request_filters,
+            request_from_buffers: repr.request_from_buffers,
+            parameters: repr.parameters,
+            alert_parameters: repr.alert_parameters,
+            auto_launch: repr.auto_launch,
+            station: repr.station,
+            switch_state: repr.switch_state,
+            manual_trains_limit: repr.manual_trains_limit,
+        };
+        Entity {
+            entity_number: repr.entity_number,
+            name: repr.name,
+            position: repr.position,
+            direction: repr.direction,
+            orientation: repr.orientation,
+            connections: repr.connections,
+            control_behavior: repr.control_behavior,
+            items: repr.items,
+            recipe: repr.recipe,
+            bar: repr.bar,
+            type_: repr.type_,
+            input_priority: repr.input_priority,
+            output_priority: repr.output_priority,
+            override_stack_size: repr.override_stack_size,
+            drop_position: repr.drop_position,
+            pickup_position: repr.pickup_position,
+            variation: repr.variation,
+            color: repr.color,
+            neighbours: repr.neighbours,
+            extra: if extra.is_empty() {
+                None
+            } else {
+                Some(Box::new(extra))
+            },
+        }
+    }
+}
+
+impl Serialize for Entity {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        struct Factorio(R64);
+
+        impl Serialize for Factorio {
+            fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+                serialize_r64(&self.0, s)
+            }
+        }
+
+        let extra = self.extra.as_deref().unwrap_or(&NO_EXTRA);
+        let orientation = self.orientation.map(Factorio);
+        // the optional fields in the order in which Factorio writes them,
+        // after the required ones; unset fields are skipped
+        macro_rules! fields {
+            ($field:ident) => {
+                $field!("direction", self.direction.as_ref());
+                $field!("orientation", orientation.as_ref());
+                $field!("connections", self.connections.as_ref());
+                $field!("control_behavior", self.control_behavior.as_ref());
+                $field!("items", self.items.as_ref());
+                $field!("recipe", self.recipe.as_ref());
+                $field!("bar", self.bar.as_ref());
+                $field!("inventory", extra.inventory.as_ref());
+                $field!("infinity_settings", extra.infinity_settings.as_ref());
+                $field!("type", self.type_.as_ref());
+                $field!("input_priority", self.input_priority.as_ref());
+                $field!("output_priority", self.output_priority.as_ref());
+                $field!("filter", extra.filter.as_ref());
+                $field!("filters", extra.filters.as_ref());
+                $field!("filter_mode", extra.filter_mode.as_ref());
+                $field!("override_stack_size", self.override_stack_size.as_ref());
+                $field!("drop_position", self.drop_position.as_ref());
+                $field!("pickup_position", self.pickup_position.as_ref());
+                $field!("request_filters", extra.request_filters.as_ref());
+                $field!("request_from_buffers", extra.request_from_buffers.as_ref());
+                $field!("parameters", extra.parameters.as_ref());
+                $field!("alert_parameters", extra.alert_parameters.as_ref());
+                $field!("auto_launch", extra.auto_launch.as_ref());
+                $field!("variation", self.variation.as_ref());
+                $field!("color", self.color.as_ref());
+                $field!("station", extra.station.as_ref());
+                $field!("switch_state", extra.switch_state.as_ref());
+                $field!("manual_trains_limit", extra.manual_trains_limit.as_ref());
+                $field!("neighbours", self.neighbours.as_ref());
+            };
+        }
+
+        let mut len = 3;
+        macro_rules! count {
+            ($key:expr, $value:expr) => {
+                len += $value.is_some() as usize
+            };
+        }
+        fields!(count);
+
+        let mut state = serializer.serialize_struct("Entity", len)?;
+        state.serialize_field("entity_number", &self.entity_number)?;
+        state.serialize_field("name", &self.name)?;
+        state.serialize_field("position", &self.position)?;
+        macro_rules! serialize {
+            ($key:expr, $value:expr) => {
+                match $value {
+                    // as `Some`, as derived implementations would, for the
+                    // formats which distinguish it
+                    value @ Some(_) => state.serialize_field($key, &value)?,
+                    None => state.skip_field($key)?,
+                }
+            };
+        }
+        fields!(serialize);
+        state.end()
+    }
 }
 
 /// Direction of an entity
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize_repr, Serialize_repr, Default)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema_repr))]
 #[repr(u8)]
 pub enum Direction {
     #[default]
@@ -321,6 +771,7 @@ pub enum Direction {
 
 /// Reverse-engineered by hand, contains circuit network metadata
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct ControlBehavior {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub connect_to_logistic_network: Option<bool>,
@@ -417,6 +868,7 @@ pub struct ControlBehavior {
 
 /// Reverse-engineered by hand, contains arithmetic combinator metadata
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct ArithmeticConditions {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub first_constant: Option<i32>,
@@ -431,46 +883,111 @@ pub struct ArithmeticConditions {
     pub output_signal: Option<SignalID>,
 }
 
+/// Implement serialization of an enum whose variants are written as symbols.
+///
+/// They are (de)serialized as strings rather than as unit variants, as some
+/// formats, such as RON, write unit variants as identifiers, which symbols
+/// are not.
+macro_rules! symbol_enum {
+    ($name:ident { $($variant:ident => $symbol:expr,)* }) => {
+        impl $name {
+            /// the symbol by which Factorio writes this
+            pub fn symbol(&self) -> &'static str {
+                match self {
+                    $($name::$variant => $symbol,)*
+                }
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.symbol())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct SymbolVisitor;
+
+                impl<'de> Visitor<'de> for SymbolVisitor {
+                    type Value = $name;
+
+                    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                        formatter.write_str(concat!("a symbol of ", stringify!($name)))
+                    }
+
+                    fn visit_str<E: de::Error>(self, symbol: &str) -> Result<$name, E> {
+                        match symbol {
+                            $($symbol => Ok($name::$variant),)*
+                            _ => Err(E::unknown_variant(symbol, &[$($symbol),*])),
+                        }
+                    }
+                }
+
+                deserializer.deserialize_str(SymbolVisitor)
+            }
+        }
+
+        #[cfg(feature = "schemars")]
+        impl JsonSchema for $name {
+            fn schema_name() -> std::borrow::Cow<'static, str> {
+                stringify!($name).into()
+            }
+
+            fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+                schemars::json_schema!({
+                    "type": "string",
+                    "enum": [$($symbol),*],
+                })
+            }
+        }
+    };
+}
+
 /// Possible operations performed by an arithmetic combinator
-#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub enum ArithmeticOperation {
     /// Addition (+)
-    #[serde(rename = "+")]
     Add,
     /// Subtraction (−)
-    #[serde(rename = "-")]
     Subtract,
     /// Multiplication (*)
-    #[serde(rename = "*")]
     Multiply,
     /// Division (/)
-    #[serde(rename = "/")]
     Divide,
     /// Modulo (%)
-    #[serde(rename = "%")]
     Modulo,
     /// Exponentiation (^)
-    #[serde(rename = "^")]
     Exponentiate,
     /// Left bit shift (<<)
-    #[serde(rename = "<<")]
     LeftShift,
     /// Right bit shift (>>)
-    #[serde(rename = ">>")]
     RightShift,
     /// Bitwise AND (&)
-    #[serde(rename = "AND")]
     And,
     /// Bitwise OR (|)
-    #[serde(rename = "OR")]
     Or,
     /// Bitwise XOR (^)
-    #[serde(rename = "XOR")]
     Xor,
 }
 
+symbol_enum!(ArithmeticOperation {
+    Add => "+",
+    Subtract => "-",
+    Multiply => "*",
+    Divide => "/",
+    Modulo => "%",
+    Exponentiate => "^",
+    LeftShift => "<<",
+    RightShift => ">>",
+    And => "AND",
+    Or => "OR",
+    Xor => "XOR",
+});
+
 /// Reverse-engineered by hand, contains constant combinator metadata
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct DeciderConditions {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub first_signal: Option<SignalID>,
@@ -486,29 +1003,33 @@ pub struct DeciderConditions {
 }
 
 /// Possible comparisons performed by decider combinator
-#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub enum DeciderComparator {
     /// "is greater than" (>)
-    #[serde(rename = ">")]
     GreaterThan,
     /// "is less than" (<)
-    #[serde(rename = "<")]
     LessThan,
     /// "greater than or equal to" (>=)
-    #[serde(rename = "≥")]
     GreaterThanOrEqual,
     /// "less than or equal to" (<=)
-    #[serde(rename = "≤")]
     LessThanOrEqual,
     /// "is equal to" (=)
-    #[serde(rename = "=")]
     Equal,
     /// "is not equal to" (!=)
-    #[serde(rename = "≠")]
     NotEqual,
 }
 
+symbol_enum!(DeciderComparator {
+    GreaterThan => ">",
+    LessThan => "<",
+    GreaterThanOrEqual => "≥",
+    LessThanOrEqual => "≤",
+    Equal => "=",
+    NotEqual => "≠",
+});
+
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct LogisticCondition {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub first_signal: Option<SignalID>,
@@ -520,6 +1041,7 @@ pub struct LogisticCondition {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize_repr, Serialize_repr)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema_repr))]
 #[repr(u32)]
 pub enum CircuitModeOfOperation {
     /// TODO what does this correspond to? power_book.txt has this set to 0
@@ -533,6 +1055,7 @@ pub enum CircuitModeOfOperation {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize_repr, Serialize_repr)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema_repr))]
 #[repr(u32)]
 pub enum ContentReadMode {
     Pulse = 0,
@@ -540,6 +1063,7 @@ pub enum ContentReadMode {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct SpeakerCircuitParameters {
     pub instrument_id: i32,
     pub note_id: i32,
@@ -547,13 +1071,19 @@ pub struct SpeakerCircuitParameters {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
-#[serde(untagged)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[serde(
+    untagged,
+    expecting = "entity connections did not match any known form: expected a map from \
+                 circuit connector id to connection"
+)]
 pub enum EntityConnections {
-    StringIdx(HashMap<String, Connection>),
-    NumberIdx(HashMap<OneBasedIndex, Connection>),
+    StringIdx(BTreeMap<String, Connection>),
+    NumberIdx(BTreeMap<OneBasedIndex, Connection>),
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum EntityType {
     Input,
@@ -562,6 +1092,7 @@ pub enum EntityType {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum EntityPriority {
     Left,
@@ -569,6 +1100,7 @@ pub enum EntityPriority {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum EntityFilterMode {
     Whitelist,
@@ -577,6 +1109,7 @@ pub enum EntityFilterMode {
 
 /// https://wiki.factorio.com/Blueprint_string_format#Inventory_object
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct Inventory {
     pub filters: Vec<ItemFilter>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -585,6 +1118,7 @@ pub struct Inventory {
 
 /// https://wiki.factorio.com/Blueprint_string_format#Schedule_object
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct Schedule {
     pub schedule: Vec<ScheduleRecord>,
     pub locomotives: Vec<EntityNumber>,
@@ -592,6 +1126,7 @@ pub struct Schedule {
 
 /// https://wiki.factorio.com/Blueprint_string_format#Schedule_Record_object
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct ScheduleRecord {
     pub station: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -600,6 +1135,7 @@ pub struct ScheduleRecord {
 
 /// https://wiki.factorio.com/Blueprint_string_format#Wait_Condition_object
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct WaitCondition {
     #[serde(rename = "type")]
     pub type_: WaitConditionType,
@@ -611,6 +1147,7 @@ pub struct WaitCondition {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum WaitConditionType {
     Time,
@@ -626,6 +1163,7 @@ pub enum WaitConditionType {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum CompareType {
     And,
@@ -633,6 +1171,7 @@ pub enum CompareType {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct CircuitCondition {
     pub comparator: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -645,6 +1184,7 @@ pub struct CircuitCondition {
 
 /// https://wiki.factorio.com/Blueprint_string_format#Tile_object
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct Tile {
     pub name: Prototype,
     pub position: Position,
@@ -652,16 +1192,24 @@ pub struct Tile {
 
 /// https://wiki.factorio.com/Blueprint_string_format#Position_object
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct Position {
-    #[serde(serialize_with = "serialize_r64")]
+    #[serde(serialize_with = "serialize_r64", deserialize_with = "deserialize_r64")]
+    #[cfg_attr(feature = "schemars", schemars(with = "f64"))]
     pub x: R64,
-    #[serde(serialize_with = "serialize_r64")]
+    #[serde(serialize_with = "serialize_r64", deserialize_with = "deserialize_r64")]
+    #[cfg_attr(feature = "schemars", schemars(with = "f64"))]
     pub y: R64,
 }
 
 /// https://wiki.factorio.com/Blueprint_string_format#Connection_object
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
-#[serde(untagged)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[serde(
+    untagged,
+    expecting = "connection did not match any known form: expected a connection point \
+                 object with `red` and/or `green` wires, or a list of connection data"
+)]
 pub enum Connection {
     Single(ConnectionPoint),
     Multiple(Vec<ConnectionData>),
@@ -669,6 +1217,7 @@ pub enum Connection {
 
 /// https://wiki.factorio.com/Blueprint_string_format#Connection_point_object
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct ConnectionPoint {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub red: Option<Vec<ConnectionData>>,
@@ -678,6 +1227,7 @@ pub struct ConnectionPoint {
 
 /// https://wiki.factorio.com/Blueprint_string_format#Connection_data_object
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct ConnectionData {
     pub entity_id: EntityNumber,
     // FIXME: this should be an enum which maps to the defined ints, but
@@ -690,13 +1240,19 @@ pub struct ConnectionData {
 
 /// https://wiki.factorio.com/Blueprint_string_format#Item_request_object
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
-#[serde(untagged)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[serde(
+    untagged,
+    expecting = "item request did not match any known form: expected a map from item \
+                 name to count, or a list of objects with `item` and `count`"
+)]
 pub enum ItemRequest {
-    Compact(HashMap<Prototype, ItemCountType>),
+    Compact(BTreeMap<Prototype, ItemCountType>),
     Verbose(Vec<ItemRequestVerbose>),
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct ItemRequestVerbose {
     pub item: Prototype,
     pub count: ItemCountType,
@@ -704,6 +1260,7 @@ pub struct ItemRequestVerbose {
 
 /// https://wiki.factorio.com/Blueprint_string_format#Item_filter_object
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct ItemFilter {
     pub name: Prototype,
     pub index: OneBasedIndex,
@@ -711,6 +1268,7 @@ pub struct ItemFilter {
 
 /// https://wiki.factorio.com/Blueprint_string_format#Infinity_settings_object
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct InfinitySettings {
     pub remove_unfiltered_items: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -719,6 +1277,7 @@ pub struct InfinitySettings {
 
 /// https://wiki.factorio.com/Blueprint_string_format#Infinity_filter_object
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct InfinityFilter {
     pub name: Prototype,
     pub count: ItemCountType,
@@ -727,6 +1286,7 @@ pub struct InfinityFilter {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[serde(rename_all = "kebab-case")]
 pub enum InfinityFilterMode {
     AtLeast,
@@ -736,6 +1296,7 @@ pub enum InfinityFilterMode {
 
 /// https://wiki.factorio.com/Blueprint_string_format#Logistic_filter_object
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct LogisticFilter {
     pub name: Prototype,
     pub index: OneBasedIndex,
@@ -744,6 +1305,7 @@ pub struct LogisticFilter {
 
 /// Reverse-engineered by hand, contains constant combinator metadata
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct ControlFilter {
     pub signal: SignalID,
     pub index: OneBasedIndex,
@@ -752,8 +1314,10 @@ pub struct ControlFilter {
 
 /// https://wiki.factorio.com/Blueprint_string_format#Speaker_parameter_object
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct SpeakerParameter {
-    #[serde(serialize_with = "serialize_r64")]
+    #[serde(serialize_with = "serialize_r64", deserialize_with = "deserialize_r64")]
+    #[cfg_attr(feature = "schemars", schemars(with = "f64"))]
     pub playback_volume: R64,
     pub playback_globally: bool,
     pub allow_polyphony: bool,
@@ -761,6 +1325,7 @@ pub struct SpeakerParameter {
 
 /// https://wiki.factorio.com/Blueprint_string_format#Speaker_alert_parameter_object
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct SpeakerAlertParameter {
     pub show_alert: bool,
     pub show_on_map: bool,
@@ -771,19 +1336,77 @@ pub struct SpeakerAlertParameter {
 
 /// https://wiki.factorio.com/Blueprint_string_format#Color_object
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct Color {
+    #[serde(serialize_with = "serialize_r64", deserialize_with = "deserialize_r64")]
+    #[cfg_attr(feature = "schemars", schemars(with = "f64"))]
     pub r: R64,
+    #[serde(serialize_with = "serialize_r64", deserialize_with = "deserialize_r64")]
+    #[cfg_attr(feature = "schemars", schemars(with = "f64"))]
     pub g: R64,
+    #[serde(serialize_with = "serialize_r64", deserialize_with = "deserialize_r64")]
+    #[cfg_attr(feature = "schemars", schemars(with = "f64"))]
     pub b: R64,
+    #[serde(serialize_with = "serialize_r64", deserialize_with = "deserialize_r64")]
+    #[cfg_attr(feature = "schemars", schemars(with = "f64"))]
     pub a: R64,
 }
 
 /// Serialize this R64 value in the same way that Factorio does
 /// If the number fractional component is 0, omit the decimal places
+///
+/// This also ensures that equal values serialize identically: `-0.0` and
+/// `0.0` both become `0`.
 fn serialize_r64<S: Serializer>(v: &R64, s: S) -> Result<S::Ok, S::Error> {
-    if v.raw().fract() == 0.0 {
+    // beyond 2^53, not every integer is representable as an f64
+    const MAX_EXACT_INTEGER: f64 = (1_u64 << 53) as f64;
+    if v.raw().fract() == 0.0 && v.raw().abs() <= MAX_EXACT_INTEGER {
         s.serialize_i64(v.raw() as i64)
     } else {
         v.serialize(s)
     }
 }
+
+/// An R64 which may have been serialized as an integer, by `serialize_r64`.
+///
+/// json does not distinguish integers from floats, but other formats do,
+/// and some refuse to read an integer as a float.
+struct AnyR64(R64);
+
+impl<'de> Deserialize<'de> for AnyR64 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<AnyR64, D::Error> {
+        struct AnyR64Visitor;
+
+        impl<'de> Visitor<'de> for AnyR64Visitor {
+            type Value = AnyR64;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a number")
+            }
+
+            fn visit_f64<E: de::Error>(self, v: f64) -> Result<AnyR64, E> {
+                R64::try_new(v)
+                    .map(AnyR64)
+                    .ok_or_else(|| E::invalid_value(de::Unexpected::Float(v), &self))
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<AnyR64, E> {
+                self.visit_f64(v as f64)
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<AnyR64, E> {
+                self.visit_f64(v as f64)
+            }
+        }
+
+        deserializer.deserialize_any(AnyR64Visitor)
+    }
+}
+
+fn deserialize_r64<'de, D: Deserializer<'de>>(d: D) -> Result<R64, D::Error> {
+    AnyR64::deserialize(d).map(|v| v.0)
+}
+
+fn deserialize_optional_r64<'de, D: Deserializer<'de>>(d: D) -> Result<Option<R64>, D::Error> {
+    Option::<AnyR64>::deserialize(d).map(|v| v.map(|v| v.0))
+}
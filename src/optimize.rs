@@ -0,0 +1,272 @@
+//! Producing the shortest blueprint string which builds the same thing.
+//!
+//! [`BlueprintCodec::encode_minimal`] first rewrites the container with
+//! [`minimize`]: it drops values which the game assumes anyway, such as a
+//! `direction` of north, a `circuit_id` of 1, or empty lists, and writes
+//! item requests in whichever of their two forms is shorter. It then tries
+//! several orders of entities and tiles, which the game does not care about
+//! but zlib does, and every compression level, and keeps the shortest
+//! result.
+//!
+//! Numbers need no further attention: whole numbers are already written
+//! without a fraction, and others in the shortest form which reads back
+//! exactly. Both forms of [`EntityConnections`] are written identically.
+//!
+//! [`EntityConnections`]: crate::objects::EntityConnections
+
+use crate::{
+    encode_options::EncodeOptions,
+    objects::{
+        Blueprint, Connection, ConnectionData, ConnectionPoint, Direction, Entity,
+        EntityConnections, ItemRequest, ItemRequestVerbose, Tile,
+    },
+    BlueprintCodec, Container, Result,
+};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+/// The result of [`BlueprintCodec::encode_minimal`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Optimized {
+    /// The shortest blueprint string found.
+    pub string: String,
+    /// The length of the container's blueprint string as encoded by
+    /// [`BlueprintCodec::encode_string`].
+    pub original_len: usize,
+    /// The compression level which produced `string`.
+    pub compression_level: u32,
+}
+
+impl Optimized {
+    /// the number of bytes by which `string` is shorter than the original
+    pub fn bytes_saved(&self) -> usize {
+        self.original_len.saturating_sub(self.string.len())
+    }
+}
+
+/// remove from the container everything which the game would assume
+/// anyway, and write the rest in its shortest form
+pub fn minimize(container: &mut Container) {
+    match container {
+        Container::BlueprintBook(book) => {
+            if book.label.as_deref() == Some("") {
+                book.label = None;
+            }
+            if book.description.as_deref() == Some("") {
+                book.description = None;
+            }
+            for page in &mut book.blueprints {
+                minimize(&mut page.item);
+            }
+        }
+        Container::Blueprint(blueprint) => minimize_blueprint(blueprint),
+        Container::DeconstructionPlanner(_) | Container::UpgradePlanner(_) => {}
+    }
+}
+
+fn minimize_blueprint(blueprint: &mut Blueprint) {
+    if blueprint.label.as_deref() == Some("") {
+        blueprint.label = None;
+    }
+    if blueprint.description.as_deref() == Some("") {
+        blueprint.description = None;
+    }
+    if blueprint.absolute_snapping == Some(false) {
+        blueprint.absolute_snapping = None;
+    }
+    for entity in &mut blueprint.entities {
+        minimize_entity(entity);
+    }
+}
+
+fn minimize_entity(entity: &mut Entity) {
+    if entity.direction == Some(Direction::North) {
+        entity.direction = None;
+    }
+    if entity
+        .orientation
+        .is_some_and(|orientation| orientation.raw() == 0.0)
+    {
+        entity.orientation = None;
+    }
+    if entity.neighbours.as_ref().is_some_and(Vec::is_empty) {
+        entity.neighbours = None;
+    }
+    entity.items = entity.items.take().and_then(minimize_items);
+    entity.connections = entity.connections.take().and_then(minimize_connections);
+}
+
+/// the shorter form of the item requests, if there are any
+fn minimize_items(items: ItemRequest) -> Option<ItemRequest> {
+    let compact = match &items {
+        ItemRequest::Compact(requests) => requests.clone(),
+        ItemRequest::Verbose(requests) => {
+            let mut compact = BTreeMap::new();
+            for request in requests {
+                // a repeated item has no compact form
+                if compact
+                    .insert(request.item.clone(), request.count)
+                    .is_some()
+                {
+                    return Some(items);
+                }
+            }
+            compact
+        }
+    };
+    if compact.is_empty() {
+        return None;
+    }
+    let verbose = compact
+        .iter()
+        .map(|(item, &count)| ItemRequestVerbose {
+            item: item.clone(),
+            count,
+        })
+        .collect();
+    let compact = ItemRequest::Compact(compact);
+    let verbose = ItemRequest::Verbose(verbose);
+    Some(if json_len(&verbose) < json_len(&compact) {
+        verbose
+    } else {
+        compact
+    })
+}
+
+fn minimize_connections(connections: EntityConnections) -> Option<EntityConnections> {
+    match connections {
+        EntityConnections::StringIdx(points) => {
+            let points = minimize_points(points);
+            (!points.is_empty()).then_some(EntityConnections::StringIdx(points))
+        }
+        EntityConnections::NumberIdx(points) => {
+            let points = minimize_points(points);
+            (!points.is_empty()).then_some(EntityConnections::NumberIdx(points))
+        }
+    }
+}
+
+fn minimize_points<K: Ord>(points: BTreeMap<K, Connection>) -> BTreeMap<K, Connection> {
+    points
+        .into_iter()
+        .filter_map(|(key, connection)| {
+            let connection = match connection {
+                Connection::Single(ConnectionPoint { red, green }) => {
+                    let red = red.and_then(minimize_wires);
+                    let green = green.and_then(minimize_wires);
+                    if red.is_none() && green.is_none() {
+                        return None;
+                    }
+                    Connection::Single(ConnectionPoint { red, green })
+                }
+                Connection::Multiple(wires) => Connection::Multiple(minimize_wires(wires)?),
+            };
+            Some((key, connection))
+        })
+        .collect()
+}
+
+fn minimize_wires(mut wires: Vec<ConnectionData>) -> Option<Vec<ConnectionData>> {
+    for wire in &mut wires {
+        // circuit connector 1 is the default
+        if wire.circuit_id == Some(1) {
+            wire.circuit_id = None;
+        }
+    }
+    (!wires.is_empty()).then_some(wires)
+}
+
+fn json_len<T: serde::Serialize>(value: &T) -> usize {
+    serde_json::to_vec(value).map_or(usize::MAX, |json| json.len())
+}
+
+type EntityOrder = fn(&Entity, &Entity) -> Ordering;
+type TileOrder = fn(&Tile, &Tile) -> Ordering;
+
+/// Orders of entities and tiles to try; `None` keeps the original order.
+const ORDERS: &[(Option<EntityOrder>, Option<TileOrder>)] = &[
+    (None, None),
+    (
+        Some(|a, b| a.entity_number.cmp(&b.entity_number)),
+        Some(|a, b| {
+            (a.position.y, a.position.x, &a.name).cmp(&(b.position.y, b.position.x, &b.name))
+        }),
+    ),
+    (
+        // entities of the same kind are much alike
+        Some(|a, b| {
+            (&a.name, a.position.y, a.position.x).cmp(&(&b.name, b.position.y, b.position.x))
+        }),
+        Some(|a, b| {
+            (&a.name, a.position.y, a.position.x).cmp(&(&b.name, b.position.y, b.position.x))
+        }),
+    ),
+];
+
+fn reorder(container: &mut Container, entities: Option<EntityOrder>, tiles: Option<TileOrder>) {
+    match container {
+        Container::BlueprintBook(book) => {
+            for page in &mut book.blueprints {
+                reorder(&mut page.item, entities, tiles);
+            }
+        }
+        Container::Blueprint(blueprint) => {
+            if let Some(order) = entities {
+                blueprint.entities.sort_by(order);
+            }
+            if let Some(order) = tiles {
+                blueprint.tiles.sort_by(order);
+            }
+        }
+        Container::DeconstructionPlanner(_) | Container::UpgradePlanner(_) => {}
+    }
+}
+
+impl BlueprintCodec {
+    /// produce the shortest blueprint string found for the container,
+    /// along with how much shorter it is than usual
+    ///
+    /// See [`optimize`](crate::optimize) for what is tried. This encodes the
+    /// container many times, so is much slower than `encode_string`.
+    pub fn encode_minimal(container: &Container) -> Result<Optimized> {
+        let original_len = Self::encode_string(container)?.len();
+
+        let mut minimal = container.clone();
+        minimize(&mut minimal);
+        let mut best: Option<(Vec<u8>, Container)> = None;
+        for &(entities, tiles) in ORDERS {
+            let mut candidate = minimal.clone();
+            reorder(&mut candidate, entities, tiles);
+            let mut string = Vec::new();
+            Self::encode(&mut string, &candidate)?;
+            if best
+                .as_ref()
+                .is_none_or(|(best, _)| string.len() < best.len())
+            {
+                best = Some((string, candidate));
+            }
+        }
+        let (mut string, minimal) = best.expect("there are orders to try");
+
+        let default_level = EncodeOptions::default().compression_level;
+        let mut compression_level = default_level;
+        for level in (1..=9).filter(|&level| level != default_level) {
+            let options = EncodeOptions {
+                compression_level: level,
+                ..EncodeOptions::default()
+            };
+            let mut candidate = Vec::new();
+            Self::encode_with(&mut candidate, &minimal, &options)?;
+            if candidate.len() < string.len() {
+                string = candidate;
+                compression_level = level;
+            }
+        }
+
+        Ok(Optimized {
+            string: String::from_utf8(string)?,
+            original_len,
+            compression_level,
+        })
+    }
+}
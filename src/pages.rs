@@ -0,0 +1,433 @@
+//! Streaming access to the pages of a blueprint book.
+//!
+//! Decoding a book into a [`Container`] builds the whole tree before any of
+//! it can be used. [`Pages`] instead reads the json of a book and yields its
+//! pages one at a time, descending into nested books, so that only a single
+//! page is ever held in memory.
+//!
+//! Only the pages themselves are yielded: the labels, icons, and other
+//! properties of the books which contain them are skipped.
+
+use crate::{
+    objects::{Blueprint, BlueprintBookBlueprintValue, DeconstructionPlanner, UpgradePlanner},
+    Container, Error, Result,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::io::{BufRead, BufReader, Read};
+
+/// A page of a blueprint book, or of a book nested within it.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+pub struct BookPage {
+    /// The position of each enclosing nested book within the `blueprints`
+    /// of its parent, outermost first; empty for pages of the book itself.
+    ///
+    /// Positions are used rather than page indices because Factorio writes
+    /// a page's index after its contents.
+    pub path: Vec<usize>,
+    #[serde(flatten)]
+    pub page: BlueprintBookBlueprintValue,
+}
+
+/// The json value which the parser is within.
+#[derive(Debug)]
+enum Frame {
+    /// The outermost object, which should hold a single book.
+    Container {
+        first: bool,
+    },
+    Book {
+        first: bool,
+    },
+    /// The `blueprints` array of a book.
+    Pages {
+        first: bool,
+        next: usize,
+    },
+    Page {
+        first: bool,
+        position: usize,
+        index: Option<usize>,
+        /// whether the page's item has been read, into `Pages::item`
+        has_item: bool,
+        /// whether the item was a nested book, whose pages were yielded
+        nested: bool,
+    },
+}
+
+/// Iterator over the pages of the blueprint book whose json is read from
+/// `R`.
+///
+/// Pages of nested books are yielded in place of the books themselves. After
+/// an error, no further pages are yielded.
+pub struct Pages<R> {
+    reader: BufReader<R>,
+    stack: Vec<Frame>,
+    /// the item of the innermost page, once read
+    item: Option<Container>,
+    started: bool,
+    done: bool,
+}
+
+/// The keys under which a page may store its item.
+const ITEMS: &[&str] = &[
+    "blueprint_book",
+    "blueprint",
+    "deconstruction_planner",
+    "upgrade_planner",
+];
+
+fn syntax_error(msg: impl std::fmt::Display) -> serde_json::Error {
+    serde::de::Error::custom(msg)
+}
+
+impl<R: Read> Pages<R> {
+    pub fn new(reader: R) -> Pages<R> {
+        Pages {
+            reader: BufReader::new(reader),
+            stack: Vec::new(),
+            item: None,
+            started: false,
+            done: false,
+        }
+    }
+
+    /// the path to the value being parsed, as reported in errors
+    fn json_path(&self) -> String {
+        let mut path = String::new();
+        for frame in &self.stack {
+            match frame {
+                Frame::Container { .. } => {}
+                Frame::Book { .. } => {
+                    if !path.is_empty() {
+                        path.push('.');
+                    }
+                    path.push_str("blueprint_book");
+                }
+                Frame::Pages { .. } => path.push_str(".blueprints"),
+                Frame::Page { position, .. } => path.push_str(&format!("[{}]", position)),
+            }
+        }
+        path
+    }
+
+    fn error(&self, source: serde_json::Error) -> Error {
+        let path = self.json_path();
+        Error::Json {
+            path: if path.is_empty() { ".".into() } else { path },
+            source,
+        }
+    }
+
+    /// the next byte which is not whitespace, without consuming it
+    fn peek(&mut self) -> Result<Option<u8>> {
+        loop {
+            let buf = self.reader.fill_buf()?;
+            match buf.first() {
+                None => return Ok(None),
+                Some(byte) if byte.is_ascii_whitespace() => self.reader.consume(1),
+                Some(&byte) => return Ok(Some(byte)),
+            }
+        }
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<()> {
+        match self.peek()? {
+            Some(byte) if byte == expected => {
+                self.reader.consume(1);
+                Ok(())
+            }
+            Some(byte) => Err(self.error(syntax_error(format_args!(
+                "expected `{}`, found `{}`",
+                char::from(expected),
+                char::from(byte)
+            )))),
+            None => Err(self.error(syntax_error(format_args!(
+                "expected `{}`, found end of input",
+                char::from(expected)
+            )))),
+        }
+    }
+
+    /// deserialize a value which does not need to look beyond its own end,
+    /// as serde_json loses any byte which it peeks at
+    fn deserialize<T: DeserializeOwned>(&mut self) -> Result<T> {
+        let mut deserializer = serde_json::Deserializer::from_reader(&mut self.reader);
+        crate::prototype::interned(|| serde_path_to_error::deserialize(&mut deserializer)).map_err(
+            |err| {
+                if err.inner().is_io() {
+                    return Error::Io(err.into_inner().into());
+                }
+                let mut path = self.json_path();
+                let inner = err.path().to_string();
+                if inner != "." {
+                    path.push('.');
+                    path.push_str(&inner);
+                }
+                Error::Json {
+                    path,
+                    source: err.into_inner(),
+                }
+            },
+        )
+    }
+
+    /// read the next key of an object, and the colon after it
+    fn key(&mut self, first: bool) -> Result<String> {
+        if !first {
+            self.expect(b',')?;
+        }
+        if self.peek()? != Some(b'"') {
+            return Err(self.error(syntax_error("expected a key")));
+        }
+        let key = self.deserialize()?;
+        self.expect(b':')?;
+        Ok(key)
+    }
+
+    fn number(&mut self) -> Result<String> {
+        let mut number = String::new();
+        while let Some(&byte) = self.reader.fill_buf()?.first() {
+            if !(byte.is_ascii_digit() || b"+-.eE".contains(&byte)) {
+                break;
+            }
+            number.push(char::from(byte));
+            self.reader.consume(1);
+        }
+        Ok(number)
+    }
+
+    fn skip_value(&mut self) -> Result<()> {
+        match self.peek()? {
+            // numbers are the only values whose end serde_json must peek past
+            Some(byte) if byte == b'-' || byte.is_ascii_digit() => {
+                let number = self.number()?;
+                serde_json::from_str::<serde::de::IgnoredAny>(&number)
+                    .map_err(|err| self.error(err))?;
+            }
+            _ => {
+                self.deserialize::<serde::de::IgnoredAny>()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn index(&mut self) -> Result<usize> {
+        self.peek()?;
+        let number = self.number()?;
+        serde_json::from_str(&number).map_err(|err| self.error(err))
+    }
+
+    /// begin a book, whose opening brace is next
+    fn open_book(&mut self) -> Result<()> {
+        self.expect(b'{')?;
+        self.stack.push(Frame::Book { first: true });
+        Ok(())
+    }
+
+    /// advance the parser until it completes a page, or runs out of book
+    fn advance(&mut self) -> Result<Option<BookPage>> {
+        loop {
+            let frame = match self.stack.last_mut() {
+                Some(frame) => frame,
+                None if self.started => {
+                    if self.peek()?.is_some() {
+                        return Err(self.error(syntax_error("trailing characters")));
+                    }
+                    return Ok(None);
+                }
+                None => {
+                    self.started = true;
+                    self.expect(b'{')?;
+                    self.stack.push(Frame::Container { first: true });
+                    continue;
+                }
+            };
+
+            if let Frame::Pages { first, next } = frame {
+                let (is_first, position) = (*first, *next);
+                if self.peek()? == Some(b']') {
+                    self.reader.consume(1);
+                    self.stack.pop();
+                    continue;
+                }
+                if !is_first {
+                    self.expect(b',')?;
+                }
+                self.expect(b'{')?;
+                if let Some(Frame::Pages { first, next }) = self.stack.last_mut() {
+                    *first = false;
+                    *next += 1;
+                }
+                self.stack.push(Frame::Page {
+                    first: true,
+                    position,
+                    index: None,
+                    has_item: false,
+                    nested: false,
+                });
+                continue;
+            }
+
+            // every other frame is an object
+            let first = match frame {
+                Frame::Container { first } | Frame::Book { first } | Frame::Page { first, .. } => {
+                    *first
+                }
+                Frame::Pages { .. } => unreachable!(),
+            };
+            if self.peek()? == Some(b'}') {
+                if let Some(Frame::Container { first: true }) = self.stack.last() {
+                    return Err(
+                        self.error(syntax_error("expected a blueprint book, found nothing"))
+                    );
+                }
+                self.reader.consume(1);
+                if let Some(page) = self.close()? {
+                    return Ok(Some(page));
+                }
+                continue;
+            }
+            let key = self.key(first)?;
+            let frame = self.stack.last_mut().expect("within an object");
+            if let Frame::Container { first } | Frame::Book { first } | Frame::Page { first, .. } =
+                frame
+            {
+                *first = false;
+            }
+            match (frame, key.as_str()) {
+                (Frame::Container { .. }, "blueprint_book") if first => self.open_book()?,
+                (Frame::Container { .. }, "blueprint_book") => {
+                    return Err(self.error(syntax_error("expected a single blueprint book")));
+                }
+                (Frame::Container { .. }, _) => {
+                    return Err(self.error(syntax_error(format_args!(
+                        "expected a blueprint book, found `{}`",
+                        key
+                    ))));
+                }
+                (Frame::Book { .. }, "blueprints") => {
+                    self.expect(b'[')?;
+                    self.stack.push(Frame::Pages {
+                        first: true,
+                        next: 0,
+                    });
+                }
+                (Frame::Page { index: Some(_), .. }, "index") => {
+                    return Err(self.error(serde::de::Error::duplicate_field("index")));
+                }
+                (Frame::Page { .. }, "index") => {
+                    let value = self.index()?;
+                    if let Some(Frame::Page { index, .. }) = self.stack.last_mut() {
+                        *index = Some(value);
+                    }
+                }
+                (
+                    Frame::Page {
+                        has_item, nested, ..
+                    },
+                    _,
+                ) if *has_item || *nested => {
+                    if ITEMS.contains(&key.as_str()) {
+                        return Err(self.error(syntax_error(
+                            "blueprint book page contains more than one item",
+                        )));
+                    }
+                    self.skip_value()?;
+                }
+                (Frame::Page { nested, .. }, "blueprint_book") => {
+                    *nested = true;
+                    self.open_book()?
+                }
+                (
+                    Frame::Page { has_item, .. },
+                    "blueprint" | "deconstruction_planner" | "upgrade_planner",
+                ) => {
+                    *has_item = true;
+                    let value = match key.as_str() {
+                        "blueprint" => Container::Blueprint(self.item::<Blueprint>(&key)?),
+                        "deconstruction_planner" => Container::DeconstructionPlanner(
+                            self.item::<DeconstructionPlanner>(&key)?,
+                        ),
+                        _ => Container::UpgradePlanner(self.item::<UpgradePlanner>(&key)?),
+                    };
+                    self.item = Some(value);
+                }
+                _ => self.skip_value()?,
+            }
+        }
+    }
+
+    /// deserialize the item of a page, which is stored under `key`
+    fn item<T: DeserializeOwned>(&mut self, key: &str) -> Result<T> {
+        self.deserialize::<T>().map_err(|err| match err {
+            Error::Json { path, source } => {
+                // the key is not yet part of the stack's path
+                let (page, inner) = path.split_at(self.json_path().len());
+                Error::Json {
+                    path: format!("{}.{}{}", page, key, inner),
+                    source,
+                }
+            }
+            err => err,
+        })
+    }
+
+    /// close the innermost object, yielding it if it was a page
+    fn close(&mut self) -> Result<Option<BookPage>> {
+        match self.stack.last_mut() {
+            Some(Frame::Page { index, nested, .. }) => {
+                let (index, nested) = (index.take(), *nested);
+                let index = match index {
+                    Some(index) => index,
+                    None => return Err(self.error(serde::de::Error::missing_field("index"))),
+                };
+                if nested {
+                    self.stack.pop();
+                    return Ok(None);
+                }
+                let item = match self.item.take() {
+                    Some(item) => item,
+                    None => {
+                        return Err(self.error(syntax_error(
+                            "blueprint book page contains none of `blueprint_book`, \
+                             `blueprint`, `deconstruction_planner` or `upgrade_planner`",
+                        )))
+                    }
+                };
+                self.stack.pop();
+                let path = self
+                    .stack
+                    .iter()
+                    .filter_map(|frame| match frame {
+                        Frame::Page { position, .. } => Some(*position),
+                        _ => None,
+                    })
+                    .collect();
+                Ok(Some(BookPage {
+                    path,
+                    page: BlueprintBookBlueprintValue { index, item },
+                }))
+            }
+            Some(_) => {
+                self.stack.pop();
+                Ok(None)
+            }
+            None => unreachable!("closed an object outside of any"),
+        }
+    }
+}
+
+impl<R: Read> Iterator for Pages<R> {
+    type Item = Result<BookPage>;
+
+    fn next(&mut self) -> Option<Result<BookPage>> {
+        if self.done {
+            return None;
+        }
+        let result = self.advance().transpose();
+        if !matches!(result, Some(Ok(_))) {
+            self.done = true;
+        }
+        result
+    }
+}
@@ -0,0 +1,248 @@
+//! Decoding and encoding which spread the pages of a book across threads.
+//!
+//! Available with the `rayon` feature. The json of each page of a book is
+//! independent of the others, so once the payload is decompressed, pages
+//! can be deserialized in parallel; likewise, pages can be serialized in
+//! parallel before being compressed. Nested books are split in the same way.
+//! Results are identical to those of the sequential methods.
+//!
+//! Pages which are not books are serialized through the object model's own
+//! `Serialize` implementations; books are written through a mirror of
+//! [`BlueprintBook`]'s, which holds the json of those pages.
+
+use crate::{
+    encode_options::EncodeOptions,
+    objects::{BlueprintBook, BlueprintBookBlueprintValue, Color, Icon},
+    prototype, BlueprintCodec, Container, Result,
+};
+use rayon::prelude::*;
+use serde::{
+    de::{MapAccess, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
+use serde_json::value::RawValue;
+use std::fmt;
+use std::io::{Read, Write};
+
+/// The fields of a json object, in order, with their values unparsed.
+struct Fields<'a>(Vec<(String, &'a RawValue)>);
+
+impl<'de: 'a, 'a> Deserialize<'de> for Fields<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct FieldsVisitor;
+
+        impl<'de> Visitor<'de> for FieldsVisitor {
+            type Value = Fields<'de>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("an object")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(
+                self,
+                mut map: A,
+            ) -> std::result::Result<Self::Value, A::Error> {
+                let mut fields = Vec::new();
+                while let Some(field) = map.next_entry()? {
+                    fields.push(field);
+                }
+                Ok(Fields(fields))
+            }
+        }
+
+        deserializer.deserialize_map(FieldsVisitor)
+    }
+}
+
+impl<'a> Fields<'a> {
+    fn parse(raw: &'a RawValue) -> Option<Fields<'a>> {
+        let fields: Fields = serde_json::from_str(raw.get()).ok()?;
+        // serde rejects duplicate fields, which would otherwise be lost here
+        let mut keys = fields.0.iter().map(|(key, _)| key).collect::<Vec<_>>();
+        keys.sort();
+        keys.dedup();
+        (keys.len() == fields.0.len()).then_some(fields)
+    }
+
+    fn remove(&mut self, key: &str) -> Option<&'a RawValue> {
+        let position = self.0.iter().position(|(field, _)| field == key)?;
+        Some(self.0.remove(position).1)
+    }
+
+    /// the json object made of the remaining fields
+    fn to_json(&self) -> String {
+        let mut json = String::from("{");
+        for (n, (key, value)) in self.0.iter().enumerate() {
+            if n > 0 {
+                json.push(',');
+            }
+            json.push_str(&serde_json::to_string(key).expect("strings serialize"));
+            json.push(':');
+            json.push_str(value.get());
+        }
+        json.push('}');
+        json
+    }
+}
+
+// Each of these returns `None` whenever the input is anything but a valid
+// book, in which case the caller falls back to the sequential path, which
+// reports errors in detail.
+
+fn parse_container(json: &str) -> Option<Container> {
+    let mut fields = Fields::parse(serde_json::from_str(json).ok()?)?;
+    let book = fields.remove("blueprint_book")?;
+    if !fields.0.is_empty() {
+        return None;
+    }
+    parse_book(book).map(Container::BlueprintBook)
+}
+
+fn parse_book(raw: &RawValue) -> Option<BlueprintBook> {
+    let mut fields = Fields::parse(raw)?;
+    let pages = match fields.remove("blueprints") {
+        Some(pages) => serde_json::from_str::<Vec<&RawValue>>(pages.get()).ok()?,
+        None => Vec::new(),
+    };
+    let mut book: BlueprintBook = serde_json::from_str(&fields.to_json()).ok()?;
+    book.blueprints = pages
+        .into_par_iter()
+        .map(|page| prototype::interned(|| parse_page(page)))
+        .collect::<Option<_>>()?;
+    Some(book)
+}
+
+fn parse_page(raw: &RawValue) -> Option<BlueprintBookBlueprintValue> {
+    let mut fields = Fields::parse(raw)?;
+    let book = match fields.remove("blueprint_book") {
+        Some(book) => book,
+        None => return serde_json::from_str(raw.get()).ok(),
+    };
+    let index = serde_json::from_str(fields.remove("index")?.get()).ok()?;
+    if fields.0.iter().any(|(key, _)| {
+        ["blueprint", "deconstruction_planner", "upgrade_planner"].contains(&key.as_str())
+    }) {
+        return None;
+    }
+    Some(BlueprintBookBlueprintValue {
+        index,
+        item: Container::BlueprintBook(parse_book(book)?),
+    })
+}
+
+/// A book whose pages, other than books, were serialized ahead of time.
+///
+/// This must serialize exactly as [`BlueprintBook`] does, field for field.
+#[derive(Serialize)]
+struct Book<'a> {
+    item: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label_color: Option<&'a Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'a str>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    blueprints: Vec<Page<'a>>,
+    #[serde(skip_serializing_if = "<[Icon]>::is_empty")]
+    icons: &'a [Icon],
+    active_index: usize,
+    version: u64,
+}
+
+/// A page of a [`Book`], as [`BlueprintBookBlueprintValue`] serializes it.
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Page<'a> {
+    Serialized(Box<RawValue>),
+    Book {
+        index: usize,
+        blueprint_book: Book<'a>,
+    },
+}
+
+/// A [`Container`] which holds a [`Book`].
+#[derive(Serialize)]
+struct BookContainer<'a> {
+    blueprint_book: Book<'a>,
+}
+
+/// serialize the pages of the book in parallel, descending into nested
+/// books
+fn serialize_book(book: &BlueprintBook) -> serde_json::Result<Book<'_>> {
+    let blueprints = book
+        .blueprints
+        .par_iter()
+        .map(|page: &BlueprintBookBlueprintValue| match &page.item {
+            Container::BlueprintBook(nested) => Ok(Page::Book {
+                index: page.index,
+                blueprint_book: serialize_book(nested)?,
+            }),
+            _ => serde_json::value::to_raw_value(page).map(Page::Serialized),
+        })
+        .collect::<serde_json::Result<_>>()?;
+    Ok(Book {
+        item: &book.item,
+        label: book.label.as_deref(),
+        label_color: book.label_color.as_ref(),
+        description: book.description.as_deref(),
+        blueprints,
+        icons: &book.icons,
+        active_index: book.active_index,
+        version: book.version,
+    })
+}
+
+/// serialize the container to json, serializing its pages in parallel
+fn to_json(container: &Container) -> serde_json::Result<Vec<u8>> {
+    match container {
+        Container::BlueprintBook(book) => serde_json::to_vec(&BookContainer {
+            blueprint_book: serialize_book(book)?,
+        }),
+        _ => serde_json::to_vec(container),
+    }
+}
+
+impl BlueprintCodec {
+    /// read the blueprint string from the given reader, deserializing the
+    /// pages of books in parallel
+    ///
+    /// The whole json payload is decompressed before any of it is
+    /// deserialized. This applies no limits, like `decode`.
+    pub fn decode_parallel<R: Read>(reader: R) -> Result<Container> {
+        let mut json = String::new();
+        Self::decode_reader(reader, |mut reader| {
+            reader.read_to_string(&mut json).map(|_| ())
+        })?;
+        match prototype::interned(|| parse_container(&json)) {
+            Some(container) => Ok(container),
+            None => Self::deserialize_json(json.as_bytes()),
+        }
+    }
+
+    /// write the blueprint string to the given writer, according to the
+    /// given options, serializing the pages of books in parallel
+    pub fn encode_parallel_with<W: Write>(
+        writer: W,
+        container: &Container,
+        options: &EncodeOptions,
+    ) -> Result<()> {
+        let canonical;
+        let container = if options.canonical {
+            let mut container = container.clone();
+            container.canonicalize();
+            canonical = container;
+            &canonical
+        } else {
+            container
+        };
+        let json = to_json(container)?;
+        Self::encode_writer_with(writer, options, |mut writer| writer.write_all(&json))
+    }
+
+    /// write the blueprint string to the given writer, serializing the
+    /// pages of books in parallel
+    pub fn encode_parallel<W: Write>(writer: W, container: &Container) -> Result<()> {
+        Self::encode_parallel_with(writer, container, &EncodeOptions::default())
+    }
+}
@@ -0,0 +1,224 @@
+//! Interned prototype names.
+//!
+//! A large blueprint names the same few dozen prototypes many thousands of
+//! times. While a blueprint is decoded, each distinct name is stored once,
+//! and every [`Prototype`] read from it is a shared reference to that
+//! copy: deserializing a name which has been seen before allocates nothing,
+//! and comparing or cloning a name is cheap.
+//!
+//! The table of names belongs to a single decode, and is dropped when it
+//! finishes, so names are freed along with the containers which use them.
+//! Nothing is kept between decodes, however many distinct names untrusted
+//! input contains.
+
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::borrow::Borrow;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Arc;
+
+thread_local! {
+    /// The names seen so far by the decode in progress on this thread, if
+    /// any.
+    static NAMES: RefCell<Option<HashSet<Arc<str>>>> = const { RefCell::new(None) };
+}
+
+/// run `f`, sharing one copy of each prototype name which it deserializes
+/// on this thread
+///
+/// Nested calls share the outermost call's table.
+pub(crate) fn interned<T>(f: impl FnOnce() -> T) -> T {
+    struct Clear(bool);
+
+    impl Drop for Clear {
+        fn drop(&mut self) {
+            if self.0 {
+                NAMES.with(|names| *names.borrow_mut() = None);
+            }
+        }
+    }
+
+    let outermost = NAMES.with(|names| {
+        let mut names = names.borrow_mut();
+        let outermost = names.is_none();
+        names.get_or_insert_with(HashSet::new);
+        outermost
+    });
+    let _clear = Clear(outermost);
+    f()
+}
+
+/// The name of a prototype, such as `assembling-machine-2` or `iron-plate`.
+///
+/// Behaves like a `&str`, to which it dereferences.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Prototype(Arc<str>);
+
+impl Prototype {
+    /// get the prototype of the given name, sharing it with the decode in
+    /// progress on this thread if there is one
+    pub fn new(name: &str) -> Prototype {
+        NAMES.with(|names| match names.borrow_mut().as_mut() {
+            Some(names) => match names.get(name) {
+                Some(interned) => Prototype(interned.clone()),
+                None => {
+                    let interned: Arc<str> = name.into();
+                    names.insert(interned.clone());
+                    Prototype(interned)
+                }
+            },
+            None => Prototype(name.into()),
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Default for Prototype {
+    fn default() -> Prototype {
+        Prototype::new("")
+    }
+}
+
+impl Deref for Prototype {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Prototype {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for Prototype {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Prototype {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl fmt::Display for Prototype {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&*self.0, f)
+    }
+}
+
+impl From<&str> for Prototype {
+    fn from(name: &str) -> Prototype {
+        Prototype::new(name)
+    }
+}
+
+impl From<String> for Prototype {
+    fn from(name: String) -> Prototype {
+        Prototype::new(&name)
+    }
+}
+
+impl From<Prototype> for String {
+    fn from(prototype: Prototype) -> String {
+        prototype.as_str().into()
+    }
+}
+
+impl PartialEq<str> for Prototype {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for Prototype {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<String> for Prototype {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl Serialize for Prototype {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Prototype {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Prototype, D::Error> {
+        struct PrototypeVisitor;
+
+        impl<'de> Visitor<'de> for PrototypeVisitor {
+            type Value = Prototype;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a prototype name")
+            }
+
+            fn visit_str<E: de::Error>(self, name: &str) -> Result<Prototype, E> {
+                Ok(Prototype::new(name))
+            }
+        }
+
+        deserializer.deserialize_str(PrototypeVisitor)
+    }
+}
+
+/// Prototypes are written as plain strings.
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for Prototype {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "Prototype".into()
+    }
+
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        String::json_schema(generator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{interned, Prototype, NAMES};
+    use std::sync::Arc;
+
+    #[test]
+    fn names_are_shared_within_a_decode() {
+        let (first, second) = interned(|| {
+            let first = Prototype::new("assembling-machine-2");
+            let second: Prototype = serde_json::from_str(r#""assembling-machine-2""#).unwrap();
+            (first, second)
+        });
+        assert!(Arc::ptr_eq(&first.0, &second.0));
+        assert_eq!(first, "assembling-machine-2");
+    }
+
+    #[test]
+    fn names_are_dropped_after_a_decode() {
+        let first = interned(|| Prototype::new("assembling-machine-2"));
+        assert!(NAMES.with(|names| names.borrow().is_none()));
+        assert_eq!(Arc::strong_count(&first.0), 1);
+        let second = Prototype::new("assembling-machine-2");
+        assert!(!Arc::ptr_eq(&first.0, &second.0));
+    }
+}
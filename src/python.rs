@@ -0,0 +1,106 @@
+//! Bindings for Python, through pyo3.
+//!
+//! Available with the `python` feature. `pyproject.toml` builds them into
+//! a `factorio_blueprint` extension module with `maturin build`; type
+//! stubs are in `factorio_blueprint.pyi`.
+//!
+//! Containers cross into Python as dicts and lists with exactly the shape
+//! of their json. Errors are raised as subclasses of `BlueprintError`, one
+//! for each [`Stage`] of decoding; errors which belong to no stage are
+//! raised as `BlueprintError` itself.
+
+use crate::{Container, Error, Stage};
+use pyo3::{create_exception, exceptions::PyException, prelude::*};
+use pythonize::{depythonize, pythonize};
+
+create_exception!(
+    factorio_blueprint,
+    BlueprintError,
+    PyException,
+    "Raised when a blueprint cannot be decoded or encoded."
+);
+create_exception!(
+    factorio_blueprint,
+    InputError,
+    BlueprintError,
+    "Raised when the input cannot be read, or is too large."
+);
+create_exception!(
+    factorio_blueprint,
+    VersionError,
+    BlueprintError,
+    "Raised when a blueprint string has an unknown version byte."
+);
+create_exception!(
+    factorio_blueprint,
+    Base64Error,
+    BlueprintError,
+    "Raised when a blueprint string is not valid base64."
+);
+create_exception!(
+    factorio_blueprint,
+    ZlibError,
+    BlueprintError,
+    "Raised when a blueprint string does not hold a valid zlib stream."
+);
+create_exception!(
+    factorio_blueprint,
+    JsonError,
+    BlueprintError,
+    "Raised when a blueprint's json does not describe a valid container."
+);
+
+impl From<Error> for PyErr {
+    fn from(err: Error) -> PyErr {
+        let message = err.to_string();
+        match err.stage() {
+            Some(Stage::Input) => InputError::new_err(message),
+            Some(Stage::Version) => VersionError::new_err(message),
+            Some(Stage::Base64) => Base64Error::new_err(message),
+            Some(Stage::Zlib) => ZlibError::new_err(message),
+            Some(Stage::Json) => JsonError::new_err(message),
+            None => BlueprintError::new_err(message),
+        }
+    }
+}
+
+/// Reads and writes blueprint strings.
+#[pyclass(name = "BlueprintCodec", module = "factorio_blueprint", frozen)]
+pub struct PyBlueprintCodec;
+
+#[pymethods]
+impl PyBlueprintCodec {
+    /// decode a blueprint string into a dict
+    #[staticmethod]
+    fn decode<'py>(py: Python<'py>, blueprint: &str) -> PyResult<Bound<'py, PyAny>> {
+        let container = py.detach(|| crate::BlueprintCodec::decode_string(blueprint))?;
+        Ok(pythonize(py, &container)?)
+    }
+
+    /// encode a dict, of the shape returned by `decode`, as a blueprint
+    /// string
+    #[staticmethod]
+    fn encode(py: Python<'_>, container: &Bound<'_, PyAny>) -> PyResult<String> {
+        let value: serde_json::Value = depythonize(container)?;
+        let encoded = py.detach(|| {
+            let container: Container =
+                serde_path_to_error::deserialize(value).map_err(Error::from)?;
+            crate::BlueprintCodec::encode_string(&container)
+        })?;
+        Ok(encoded)
+    }
+}
+
+/// The `factorio_blueprint` Python module.
+#[pymodule]
+pub fn factorio_blueprint(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = module.py();
+    module.add_class::<PyBlueprintCodec>()?;
+    module.add("BlueprintError", py.get_type::<BlueprintError>())?;
+    module.add("InputError", py.get_type::<InputError>())?;
+    module.add("VersionError", py.get_type::<VersionError>())?;
+    module.add("Base64Error", py.get_type::<Base64Error>())?;
+    module.add("ZlibError", py.get_type::<ZlibError>())?;
+    module.add("JsonError", py.get_type::<JsonError>())?;
+    Ok(())
+}
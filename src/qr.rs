@@ -0,0 +1,228 @@
+//! QR codes of blueprint strings, for printing.
+//!
+//! Available with the `qr` feature. A blueprint string which fits in a
+//! single code is stored as it is, so any scanner can read it. Longer
+//! strings are split across a numbered sequence of codes, each of which
+//! holds a chunk of the string prefixed by its part number and the number of
+//! parts, as in `2/5:eNrtvW…`; base64 has no `/` followed by digits and a
+//! `:`, so the prefix cannot be mistaken for part of the string.
+//!
+//! Codes are generated with medium error correction, which survives some
+//! creasing and smudging of the paper.
+
+use crate::{encode_options::EncodeOptions, BlueprintCodec, Container, Error, Result};
+use image::{ImageFormat, Luma};
+use qrcode::{bits::Bits, render::svg, EcLevel, QrCode, Version};
+use std::io::Cursor;
+
+/// The most bytes which a code can hold, at medium error correction.
+pub const MAX_CHUNK_LEN: usize = 2331;
+
+/// The most codes which a sequence may have. Even at the default chunk
+/// length, this holds a blueprint string of 4 MB, more than anyone would
+/// print.
+pub const MAX_PARTS: usize = 4096;
+
+/// Options controlling how QR codes are generated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QrOptions {
+    /// The most bytes to store in each code, counting the part number
+    /// prefix of a sequence. Smaller codes are easier to scan; the largest
+    /// possible is [`MAX_CHUNK_LEN`].
+    pub chunk_len: usize,
+    /// The width and height of each module, the squares which make up a
+    /// code, in pixels for PNG and in user units for SVG.
+    pub module_size: u32,
+}
+
+impl Default for QrOptions {
+    fn default() -> QrOptions {
+        QrOptions {
+            chunk_len: 1000,
+            module_size: 4,
+        }
+    }
+}
+
+/// the texts to store in each code of the sequence for the blueprint string
+pub fn payloads(blueprint: &str, options: &QrOptions) -> Vec<String> {
+    if blueprint.len() <= options.chunk_len {
+        return vec![blueprint.to_string()];
+    }
+    // leave room for the longest prefix, which grows with the number of
+    // chunks which the room left over makes for
+    let mut count = 1;
+    let chunk_len = loop {
+        let prefix_len = format!("{}/{}:", count, count).len();
+        let chunk_len = options.chunk_len.saturating_sub(prefix_len).max(1);
+        let needed = blueprint.len().div_ceil(chunk_len);
+        if needed <= count {
+            break chunk_len;
+        }
+        count = needed;
+    };
+    // base64 is ascii, so every byte is a character boundary
+    let chunks = blueprint.as_bytes().chunks(chunk_len);
+    let count = chunks.len();
+    chunks
+        .enumerate()
+        .map(|(n, chunk)| {
+            let chunk = std::str::from_utf8(chunk).expect("blueprint strings are ascii");
+            format!("{}/{}:{}", n + 1, count, chunk)
+        })
+        .collect()
+}
+
+fn codes(container: &Container, options: &QrOptions) -> Result<Vec<QrCode>> {
+    let blueprint = BlueprintCodec::encode_string_with(container, &EncodeOptions::default())?;
+    let payloads = payloads(&blueprint, options);
+    if payloads.len() > MAX_PARTS {
+        return Err(Error::Qr(format!(
+            "{} codes are needed, more than the limit of {}",
+            payloads.len(),
+            MAX_PARTS
+        )));
+    }
+    payloads.iter().map(|payload| code(payload)).collect()
+}
+
+/// the smallest code which holds the payload
+///
+/// The payload is stored in byte mode, for which [`MAX_CHUNK_LEN`] is the
+/// capacity of the largest code. Mixing modes, as `qrcode` would otherwise,
+/// sometimes needs more room for a base64 string, not less.
+fn code(payload: &str) -> Result<QrCode> {
+    for version in 1..=40 {
+        let mut bits = Bits::new(Version::Normal(version));
+        if bits.push_byte_data(payload.as_bytes()).is_ok()
+            && bits.push_terminator(EcLevel::M).is_ok()
+        {
+            return QrCode::with_bits(bits, EcLevel::M).map_err(|err| Error::Qr(err.to_string()));
+        }
+    }
+    Err(Error::Qr(format!(
+        "{} bytes do not fit in a code, which holds at most {}",
+        payload.len(),
+        MAX_CHUNK_LEN
+    )))
+}
+
+/// generate the sequence of QR codes for the container, as SVG documents
+pub fn to_svg(container: &Container, options: &QrOptions) -> Result<Vec<String>> {
+    Ok(codes(container, options)?
+        .iter()
+        .map(|code| {
+            code.render::<svg::Color>()
+                .module_dimensions(options.module_size, options.module_size)
+                .build()
+        })
+        .collect())
+}
+
+/// generate the sequence of QR codes for the container, as PNG images
+pub fn to_png(container: &Container, options: &QrOptions) -> Result<Vec<Vec<u8>>> {
+    codes(container, options)?
+        .iter()
+        .map(|code| {
+            let image = code
+                .render::<Luma<u8>>()
+                .module_dimensions(options.module_size, options.module_size)
+                .build();
+            let mut png = Vec::new();
+            image
+                .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+                .map_err(|err| Error::Qr(err.to_string()))?;
+            Ok(png)
+        })
+        .collect()
+}
+
+/// the part number, the number of parts, and the chunk of a payload which
+/// is part of a sequence
+fn parse_part(payload: &str) -> Option<(usize, usize, &str)> {
+    let (numbers, chunk) = payload.split_once(':')?;
+    let (part, count) = numbers.split_once('/')?;
+    Some((part.parse().ok()?, count.parse().ok()?, chunk))
+}
+
+/// reassemble and decode the blueprint string stored in the texts of a
+/// sequence of QR codes, which may be in any order
+///
+/// Repeated parts, as from scanning a code twice, are ignored.
+pub fn from_payloads<I, S>(payloads: I) -> Result<Container>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut parts: Vec<Option<String>> = Vec::new();
+    for payload in payloads {
+        let payload = payload.as_ref().trim();
+        let (part, count, chunk) = match parse_part(payload) {
+            Some(part) => part,
+            // a string which needed only one code
+            None => (1, 1, payload),
+        };
+        if count > MAX_PARTS {
+            return Err(Error::Qr(format!(
+                "part {}/{} is of a sequence of more than {} codes",
+                part, count, MAX_PARTS
+            )));
+        }
+        if parts.is_empty() {
+            parts.resize(count, None);
+        }
+        if part == 0 || part > count || count != parts.len() {
+            return Err(Error::Qr(format!(
+                "part {}/{} does not belong to a sequence of {} codes",
+                part,
+                count,
+                parts.len()
+            )));
+        }
+        parts[part - 1] = Some(chunk.to_string());
+    }
+    let mut blueprint = String::new();
+    for (n, part) in parts.iter().enumerate() {
+        let chunk = part.as_ref().ok_or(Error::QrMissing {
+            part: n + 1,
+            count: parts.len(),
+        })?;
+        blueprint.push_str(chunk);
+    }
+    if blueprint.is_empty() {
+        return Err(Error::Qr("no QR codes were given".into()));
+    }
+    BlueprintCodec::decode_string(&blueprint)
+}
+
+/// the texts of every QR code found in an image, in any format which the
+/// `image` crate was built to read
+pub fn scan(image: &[u8]) -> Result<Vec<String>> {
+    let image = image::load_from_memory(image)
+        .map_err(|err| Error::Qr(err.to_string()))?
+        .to_luma8();
+    let mut prepared = rqrr::PreparedImage::prepare(image);
+    prepared
+        .detect_grids()
+        .iter()
+        .map(|grid| {
+            grid.decode()
+                .map(|(_, content)| content)
+                .map_err(|err| Error::Qr(err.to_string()))
+        })
+        .collect()
+}
+
+/// read the container stored in a sequence of QR code images, which may
+/// be in any order, and each of which may hold several codes
+pub fn from_images<I, B>(images: I) -> Result<Container>
+where
+    I: IntoIterator<Item = B>,
+    B: AsRef<[u8]>,
+{
+    let mut payloads = Vec::new();
+    for image in images {
+        payloads.extend(scan(image.as_ref())?);
+    }
+    from_payloads(payloads)
+}
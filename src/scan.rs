@@ -0,0 +1,198 @@
+//! Find blueprint strings buried in arbitrary text.
+//!
+//! Forum posts, chat logs, and wiki pages mix blueprint strings with prose.
+//! Every blueprint string starts with the version byte `0`, followed by the
+//! base64 encoding of a zlib header: `eN` at Factorio's compression level, or
+//! `eA`, `eF`, or `eJ` at others. The scanner looks for runs of base64
+//! characters starting there, and follows them across line breaks when they
+//! appear to have been wrapped.
+
+use crate::{limits::DecodeLimits, BlueprintCodec, Container, Result};
+use std::ops::Range;
+
+/// The version byte and the start of a base64 zlib header, whose last
+/// character varies with the compression level.
+const PREFIXES: &[&[u8]] = &[b"0eN", b"0eJ", b"0eF", b"0eA"];
+const PREFIX_LEN: usize = 3;
+
+fn is_prefix(bytes: &[u8]) -> bool {
+    PREFIXES.iter().any(|prefix| bytes.starts_with(prefix))
+}
+
+fn is_base64(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'+' || byte == b'/' || byte == b'='
+}
+
+fn is_horizontal_space(byte: u8) -> bool {
+    byte == b' ' || byte == b'\t' || byte == b'\r'
+}
+
+/// A run of base64 which might be a blueprint string.
+struct Candidate {
+    start: usize,
+    /// The end of each line of the run, in order. Only the last line is
+    /// certainly part of the run; the others are fallbacks in case the run
+    /// swallowed some prose which only looked like base64.
+    ends: Vec<usize>,
+}
+
+/// Iterator over the candidate blueprint strings in some text.
+struct Candidates<'a> {
+    text: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Candidates<'a> {
+    fn skip_bytes(&self, mut idx: usize, predicate: impl Fn(u8) -> bool) -> usize {
+        while idx < self.text.len() && predicate(self.text[idx]) {
+            idx += 1;
+        }
+        idx
+    }
+
+    /// The end of the line which a wrapped run continues onto, if it does.
+    ///
+    /// A run continues only if nothing but whitespace follows it on its line,
+    /// and the next line consists of nothing but base64.
+    fn continuation(&self, end: usize) -> Option<usize> {
+        if self.text[..end].ends_with(b"=") {
+            // padding only appears at the very end
+            return None;
+        }
+        let newline = self.skip_bytes(end, is_horizontal_space);
+        if self.text.get(newline) != Some(&b'\n') {
+            return None;
+        }
+        let start = self.skip_bytes(newline + 1, is_horizontal_space);
+        let line_end = self.skip_bytes(start, is_base64);
+        if line_end == start || is_prefix(&self.text[start..]) {
+            // a line starting with the prefix is much more likely to be the
+            // next blueprint string than a continuation of this one
+            return None;
+        }
+        let after = self.skip_bytes(line_end, is_horizontal_space);
+        match self.text.get(after) {
+            None | Some(b'\n') => Some(line_end),
+            Some(_) => None,
+        }
+    }
+}
+
+impl<'a> Iterator for Candidates<'a> {
+    type Item = Candidate;
+
+    fn next(&mut self) -> Option<Candidate> {
+        loop {
+            let start = self.position
+                + self.text[self.position..]
+                    .windows(PREFIX_LEN)
+                    .position(is_prefix)?;
+            self.position = start + 1;
+            if start > 0 && is_base64(self.text[start - 1]) {
+                // the middle of some other token
+                continue;
+            }
+
+            let mut ends = vec![self.skip_bytes(start, is_base64)];
+            while let Some(end) = self.continuation(*ends.last().unwrap()) {
+                ends.push(end);
+            }
+            self.position = *ends.last().unwrap();
+            return Some(Candidate { start, ends });
+        }
+    }
+}
+
+/// The end of the blueprint string which starts at `start` and encodes
+/// `compressed` bytes, which may be well before the end of its candidate.
+fn string_end(text: &[u8], start: usize, compressed: u64) -> usize {
+    // the version byte, then the unpadded base64 of the compressed bytes
+    let mut remaining = 1 + (compressed as usize * 4).div_ceil(3);
+    let mut end = start;
+    while remaining > 0 && end < text.len() {
+        if is_base64(text[end]) {
+            remaining -= 1;
+        }
+        end += 1;
+    }
+    while text.get(end) == Some(&b'=') {
+        end += 1;
+    }
+    end
+}
+
+/// Find and decode every blueprint string in `text`.
+///
+/// See [`BlueprintCodec::find_all`].
+pub(crate) fn find_all(text: &str) -> impl Iterator<Item = (Range<usize>, Result<Container>)> + '_ {
+    let decode = move |range: Range<usize>| {
+        let decoded = BlueprintCodec::decode_measured(
+            text[range.clone()].as_bytes(),
+            &DecodeLimits::default(),
+        );
+        match decoded {
+            Ok((container, compressed)) => {
+                // decoding ignores anything after the end of the zlib stream,
+                // which the candidate may have swallowed
+                let end = string_end(text.as_bytes(), range.start, compressed);
+                (range.start..end, Ok(container))
+            }
+            Err(err) => (range, Err(err)),
+        }
+    };
+    Candidates {
+        text: text.as_bytes(),
+        position: 0,
+    }
+    .map(move |candidate| {
+        let full = decode(candidate.start..*candidate.ends.last().unwrap());
+        if full.1.is_ok() {
+            return full;
+        }
+        // swallowed prose may also break decoding: try shorter runs
+        candidate.ends[..candidate.ends.len() - 1]
+            .iter()
+            .rev()
+            .map(|&end| decode(candidate.start..end))
+            .find(|(_, result)| result.is_ok())
+            .unwrap_or(full)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Candidates;
+
+    fn candidates(text: &str) -> Vec<(usize, Vec<usize>)> {
+        Candidates {
+            text: text.as_bytes(),
+            position: 0,
+        }
+        .map(|candidate| (candidate.start, candidate.ends))
+        .collect()
+    }
+
+    #[test]
+    fn finds_runs_in_prose() {
+        assert_eq!(
+            candidates("try 0eNabc= or (0eNdef) but not x0eNghi"),
+            vec![(4, vec![11]), (16, vec![22])]
+        );
+    }
+
+    #[test]
+    fn follows_wrapped_lines() {
+        assert_eq!(
+            candidates("here:\n0eNab\n  cdef \r\nghi=\nthanks"),
+            vec![(6, vec![11, 18, 25])]
+        );
+    }
+
+    #[test]
+    fn stops_at_prose_and_new_strings() {
+        assert_eq!(
+            candidates("0eNab\nsome words\n0eNcd\n0eJef"),
+            vec![(0, vec![5]), (17, vec![22]), (23, vec![28])]
+        );
+    }
+}
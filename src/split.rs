@@ -0,0 +1,202 @@
+//! Splitting a blueprint book into several, each of whose strings fits a
+//! size limit, such as that of a chat message.
+//!
+//! Pages are packed into parts in order of their index, each part a copy of
+//! the book with the same label, description, icons, and version. Each
+//! part's label ends with its number, such as `Mall (2/3)`. A nested book
+//! which is too large to fit in a part of its own is split in the same way,
+//! and its parts kept together where they fit. Page indices are renumbered
+//! within each part.
+
+use crate::{
+    encode_options::EncodeOptions,
+    objects::{BlueprintBook, BlueprintBookBlueprintValue},
+    BlueprintCodec, Container, Error, Result,
+};
+use std::io::{self, Write};
+
+/// split the book into books whose blueprint strings, encoded with the
+/// given options, are at most `max_len` bytes long
+///
+/// A book which already fits is returned as it is. Fails with
+/// [`Error::PageTooLarge`] if a page which is not a book cannot fit in a
+/// part on its own, and with [`Error::BookTooLarge`] if a book's label,
+/// description, and icons alone do not fit.
+pub fn split_book(
+    book: &BlueprintBook,
+    max_len: usize,
+    options: &EncodeOptions,
+) -> Result<Vec<BlueprintBook>> {
+    let measure = |book: &BlueprintBook| encoded_len(book, options);
+    if measure(book)? <= max_len {
+        return Ok(vec![book.clone()]);
+    }
+    // parts are packed before their labels are numbered, so a part can
+    // overflow by a few bytes; packing again with less room fixes that. The
+    // budget shrinks every time, so this ends, at the latest when a book no
+    // longer fits without pages
+    let mut budget = max_len;
+    loop {
+        let parts = split_pages(book, &mut Vec::new(), budget, &measure)?;
+        let longest = parts
+            .iter()
+            .map(measure)
+            .try_fold(0, |longest, len| len.map(|len| longest.max(len)))?;
+        if longest <= max_len {
+            return Ok(parts);
+        }
+        budget = budget.saturating_sub(longest - max_len);
+    }
+}
+
+/// the length of the blueprint string of the book
+fn encoded_len(book: &BlueprintBook, options: &EncodeOptions) -> Result<usize> {
+    struct Counter(usize);
+
+    impl Write for Counter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0 += buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut counter = Counter(0);
+    let container = Container::BlueprintBook(book.clone());
+    BlueprintCodec::encode_with(&mut counter, &container, options)?;
+    Ok(counter.0)
+}
+
+/// a copy of the book with only the given pages
+fn with_pages(book: &BlueprintBook, pages: Vec<BlueprintBookBlueprintValue>) -> BlueprintBook {
+    BlueprintBook {
+        label: book.label.clone(),
+        label_color: book.label_color.clone(),
+        description: book.description.clone(),
+        icons: book.icons.clone(),
+        item: book.item.clone(),
+        version: book.version,
+        blueprints: pages,
+        active_index: 0,
+    }
+}
+
+/// split the pages of `book` into parts for which `measure` is within
+/// `budget`
+///
+/// `path` holds the positions of the enclosing nested books, for errors.
+fn split_pages(
+    book: &BlueprintBook,
+    path: &mut Vec<usize>,
+    budget: usize,
+    measure: &dyn Fn(&BlueprintBook) -> Result<usize>,
+) -> Result<Vec<BlueprintBook>> {
+    let overhead = measure(&with_pages(book, Vec::new()))?;
+    if overhead > budget {
+        return Err(Error::BookTooLarge {
+            path: path.clone(),
+            len: overhead,
+            limit: budget,
+        });
+    }
+
+    let mut sorted = book.blueprints.iter().enumerate().collect::<Vec<_>>();
+    sorted.sort_by_key(|(_, page)| page.index);
+
+    // first make sure that every page fits on its own, measuring each
+    let mut pages = Vec::new();
+    for (position, page) in sorted {
+        let len = measure(&with_pages(book, vec![page.clone()]))?;
+        if len <= budget {
+            pages.push((page.clone(), len));
+            continue;
+        }
+        path.push(position);
+        let nested = match &page.item {
+            Container::BlueprintBook(nested) => nested,
+            _ => {
+                return Err(Error::PageTooLarge {
+                    path: path.clone(),
+                    len,
+                    limit: budget,
+                })
+            }
+        };
+        let as_page = |part: BlueprintBook| BlueprintBookBlueprintValue {
+            index: page.index,
+            item: Container::BlueprintBook(part),
+        };
+        let measure_nested =
+            |part: &BlueprintBook| measure(&with_pages(book, vec![as_page(part.clone())]));
+        for part in split_pages(nested, path, budget, &measure_nested)? {
+            let len = measure_nested(&part)?;
+            pages.push((as_page(part), len));
+        }
+        path.pop();
+    }
+
+    // then pack them into as few parts as will hold them, in order. Pages
+    // compress better together than apart, so the sum of their lengths
+    // overestimates a part's; parts are only encoded to confirm that a page
+    // which the estimate would not allow does in fact fit
+    let mut parts = Vec::new();
+    let mut current = Vec::new();
+    let mut estimate = overhead;
+    for (page, len) in pages {
+        let added = len.saturating_sub(overhead);
+        current.push(page);
+        if current.len() == 1 || estimate + added <= budget {
+            estimate += added;
+            continue;
+        }
+        let actual = measure(&with_pages(book, current.clone()))?;
+        if actual <= budget {
+            estimate = actual;
+            continue;
+        }
+        let page = current.pop().expect("just pushed");
+        parts.push(with_pages(book, std::mem::take(&mut current)));
+        current.push(page);
+        estimate = len;
+    }
+    parts.push(with_pages(book, current));
+    number(&mut parts);
+    Ok(parts)
+}
+
+/// add its number to the label of each part, and renumber its pages
+fn number(parts: &mut [BlueprintBook]) {
+    let count = parts.len();
+    for (n, part) in parts.iter_mut().enumerate() {
+        for (index, page) in part.blueprints.iter_mut().enumerate() {
+            page.index = index;
+        }
+        if count > 1 {
+            let number = format!("({}/{})", n + 1, count);
+            part.label = Some(match part.label.take() {
+                Some(label) => format!("{} {}", label, number),
+                None => number,
+            });
+        }
+    }
+}
+
+impl BlueprintCodec {
+    /// encode the book as one or more blueprint strings, splitting it so
+    /// that each is at most `max_len` bytes long
+    ///
+    /// See [`split`](crate::split) for how books are split.
+    pub fn encode_split(
+        book: &BlueprintBook,
+        max_len: usize,
+        options: &EncodeOptions,
+    ) -> Result<Vec<String>> {
+        split_book(book, max_len, options)?
+            .into_iter()
+            .map(|part| Self::encode_string_with(&Container::BlueprintBook(part), options))
+            .collect()
+    }
+}
@@ -0,0 +1,105 @@
+//! Bookkeeping which lets decoding errors name the pipeline stage at which
+//! they occurred.
+//!
+//! Errors raised deep inside the reader pipeline all surface as
+//! `std::io::Error`. Where a stage knows that it produced an error, it tags
+//! it with a `StageError`, which later stages pass through untouched.
+
+use std::fmt;
+use std::io;
+
+/// A stage of the blueprint string decoding pipeline.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Stage {
+    /// Reading bytes from the underlying reader.
+    Input,
+    /// Checking the version byte which prefixes the string.
+    Version,
+    /// Decoding base64.
+    Base64,
+    /// Decompressing the zlib stream.
+    Zlib,
+    /// Deserializing the json payload.
+    Json,
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Stage::Input => "input",
+            Stage::Version => "version",
+            Stage::Base64 => "base64",
+            Stage::Zlib => "zlib",
+            Stage::Json => "json",
+        })
+    }
+}
+
+/// An io error tagged with the stage which produced it.
+#[derive(Debug)]
+pub(crate) struct StageError {
+    pub stage: Stage,
+    /// Stage-specific offset: see the producer of the tag for its meaning.
+    pub offset: u64,
+    pub source: io::Error,
+}
+
+impl fmt::Display for StageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} stage failed: {}", self.stage, self.source)
+    }
+}
+
+impl std::error::Error for StageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// true when this error has already been tagged by some stage
+pub(crate) fn is_tagged(err: &io::Error) -> bool {
+    err.get_ref().is_some_and(|inner| inner.is::<StageError>())
+}
+
+/// tag an error with the stage which produced it, unless an earlier stage
+/// already claimed it
+pub(crate) fn tag(stage: Stage, offset: u64, err: io::Error) -> io::Error {
+    if is_tagged(&err) {
+        return err;
+    }
+    io::Error::new(
+        err.kind(),
+        StageError {
+            stage,
+            offset,
+            source: err,
+        },
+    )
+}
+
+/// tag an error which emerged from the decompressor, which had consumed
+/// `compressed` bytes at the time
+///
+/// Base64 errors are recognizable by their inner error; anything else which
+/// no earlier stage claimed came from zlib.
+pub(crate) fn tag_decompression(compressed: u64, err: io::Error) -> io::Error {
+    let is_base64 = err
+        .get_ref()
+        .is_some_and(|inner| inner.is::<base64::DecodeError>());
+    if is_base64 {
+        tag(Stage::Base64, 0, err)
+    } else {
+        tag(Stage::Zlib, compressed, err)
+    }
+}
+
+/// recover the tag from an error, if it has one
+pub(crate) fn untag(err: io::Error) -> std::result::Result<StageError, io::Error> {
+    if !is_tagged(&err) {
+        return Err(err);
+    }
+    let inner = err.into_inner().expect("tagged errors have an inner error");
+    Ok(*inner
+        .downcast::<StageError>()
+        .expect("tagged errors contain a StageError"))
+}
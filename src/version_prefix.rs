@@ -8,6 +8,7 @@ struct VersionPrefix<W> {
 
 impl<W> VersionPrefix<W> {
     fn new(version: char, wrapped: W) -> Self {
+        assert!(version.is_ascii(), "version byte must be ascii");
         VersionPrefix {
             version: version as u8,
             has_seen_version: false,
@@ -18,10 +19,12 @@ impl<W> VersionPrefix<W> {
 
 pub struct VersionPrefixWriter<W>(VersionPrefix<W>);
 
-impl<W> VersionPrefixWriter<W>
-where
-    W: Write,
-{
+impl<W> VersionPrefixWriter<W> {
+    /// wrap a writer, prefixing it with the version byte
+    ///
+    /// # Panics
+    ///
+    /// Panics if `version` is not ascii, as the version is a single byte.
     pub fn new(version: char, wrapped: W) -> VersionPrefixWriter<W> {
         VersionPrefixWriter(VersionPrefix::new(version, wrapped))
     }
@@ -32,15 +35,11 @@ where
     W: Write,
 {
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        if self.0.has_seen_version {
-            self.0.wrapped.write(buf)
-        } else {
+        if !self.0.has_seen_version {
+            self.0.wrapped.write_all(&[self.0.version])?;
             self.0.has_seen_version = true;
-            let mut new_buf = vec![0; buf.len() + 1];
-            new_buf[0] = self.0.version;
-            new_buf[1..].copy_from_slice(buf);
-            self.0.wrapped.write(&new_buf).map(|n| n - 1)
         }
+        self.0.wrapped.write(buf)
     }
 
     fn flush(&mut self) -> std::io::Result<()> {
@@ -50,10 +49,12 @@ where
 
 pub struct VersionPrefixReader<R>(VersionPrefix<R>, Option<u8>);
 
-impl<R> VersionPrefixReader<R>
-where
-    R: Read,
-{
+impl<R> VersionPrefixReader<R> {
+    /// wrap a reader whose first byte is expected to be the version byte
+    ///
+    /// # Panics
+    ///
+    /// Panics if `version` is not ascii, as the version is a single byte.
     pub fn new(version: char, wrapped: R) -> VersionPrefixReader<R> {
         VersionPrefixReader(VersionPrefix::new(version, wrapped), None)
     }
@@ -63,6 +64,16 @@ where
     pub fn had_expected_version(&self) -> Option<bool> {
         self.1.map(|found| found == self.0.version)
     }
+
+    /// return the first byte read, if any bytes have been read
+    pub fn found_version(&self) -> Option<u8> {
+        self.1
+    }
+
+    /// get a reference to the underlying reader
+    pub fn get_ref(&self) -> &R {
+        &self.0.wrapped
+    }
 }
 
 impl<R> Read for VersionPrefixReader<R>
@@ -79,3 +90,75 @@ where
         self.0.wrapped.read(buf)
     }
 }
+
+#[cfg(feature = "async")]
+mod async_impls {
+    use super::{VersionPrefixReader, VersionPrefixWriter};
+    use std::io::{Error, ErrorKind, Result};
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+    use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+    impl<W> AsyncWrite for VersionPrefixWriter<W>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<Result<usize>> {
+            let this = &mut self.get_mut().0;
+            while !this.has_seen_version {
+                let version = [this.version];
+                match Pin::new(&mut this.wrapped).poll_write(cx, &version) {
+                    Poll::Ready(Ok(0)) => return Poll::Ready(Err(ErrorKind::WriteZero.into())),
+                    Poll::Ready(Ok(_)) => this.has_seen_version = true,
+                    Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                    Poll::Pending => return Poll::Pending,
+                }
+            }
+            Pin::new(&mut this.wrapped).poll_write(cx, buf)
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+            Pin::new(&mut self.get_mut().0.wrapped).poll_flush(cx)
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+            Pin::new(&mut self.get_mut().0.wrapped).poll_shutdown(cx)
+        }
+    }
+
+    impl<R> AsyncRead for VersionPrefixReader<R>
+    where
+        R: AsyncRead + Unpin,
+    {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<Result<()>> {
+            let this = self.get_mut();
+            if !this.0.has_seen_version {
+                let mut head = [0];
+                let mut head_buf = ReadBuf::new(&mut head);
+                match Pin::new(&mut this.0.wrapped).poll_read(cx, &mut head_buf) {
+                    Poll::Ready(Ok(())) if head_buf.filled().is_empty() => {
+                        return Poll::Ready(Err(Error::new(
+                            ErrorKind::UnexpectedEof,
+                            "failed to fill whole buffer",
+                        )))
+                    }
+                    Poll::Ready(Ok(())) => {
+                        this.0.has_seen_version = true;
+                        this.1 = Some(head[0]);
+                    }
+                    Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                    Poll::Pending => return Poll::Pending,
+                }
+            }
+            Pin::new(&mut this.0.wrapped).poll_read(cx, buf)
+        }
+    }
+}
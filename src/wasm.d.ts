@@ -0,0 +1,316 @@
+export type Container =
+    | { blueprint_book: BlueprintBook }
+    | { blueprint: Blueprint }
+    | { deconstruction_planner: DeconstructionPlanner }
+    | { upgrade_planner: UpgradePlanner };
+
+export interface BlueprintBook {
+    item: string;
+    label?: string;
+    label_color?: Color;
+    description?: string;
+    blueprints?: BlueprintBookBlueprintValue[];
+    icons?: Icon[];
+    active_index: number;
+    version: number;
+}
+
+export type BlueprintBookBlueprintValue = { index: number } & Container;
+
+export interface Blueprint {
+    item: string;
+    label?: string;
+    label_color?: Color;
+    description?: string;
+    entities?: Entity[];
+    tiles?: Tile[];
+    icons?: Icon[];
+    schedules?: Schedule[];
+    "position-relative-to-grid"?: Position;
+    "snap-to-grid"?: Position;
+    "absolute-snapping"?: boolean;
+    version: number;
+}
+
+export interface DeconstructionPlanner {
+    item: string;
+    label?: string;
+    settings?: DeconstructionSettings | null;
+    version: number;
+}
+
+export interface DeconstructionSettings {
+    description?: string;
+    icons?: Icon[];
+    entity_filters?: DeconstructionFilter[];
+    /** 0 for a whitelist, 1 for a blacklist */
+    entity_filter_mode?: 0 | 1;
+    trees_and_rocks_only?: boolean;
+    tile_filters?: DeconstructionFilter[];
+    /** 0 normal, 1 always, 2 never, 3 only */
+    tile_selection_mode?: 0 | 1 | 2 | 3;
+}
+
+export interface DeconstructionFilter {
+    index: number;
+    name: string;
+}
+
+export interface UpgradePlanner {
+    item: string;
+    settings?: UpgradePlannerSettings | null;
+    label?: string;
+    version: number;
+}
+
+export interface UpgradePlannerSettings {
+    mappers?: Mapper[];
+    description?: string;
+    icons?: Icon[];
+}
+
+export interface Mapper {
+    from?: SimpleEntity;
+    to?: SimpleEntity;
+    index: number;
+}
+
+export interface SimpleEntity {
+    type: string;
+    name?: string;
+}
+
+export interface Icon {
+    index: number;
+    signal: SignalID;
+}
+
+export interface SignalID {
+    name: string;
+    type: "item" | "fluid" | "virtual";
+}
+
+export interface Position {
+    x: number;
+    y: number;
+}
+
+export interface Color {
+    r: number;
+    g: number;
+    b: number;
+    a: number;
+}
+
+export interface Tile {
+    name: string;
+    position: Position;
+}
+
+export interface Entity {
+    entity_number: number;
+    name: string;
+    position: Position;
+    /** 0 is north, counting clockwise in eighths of a turn */
+    direction?: 0 | 1 | 2 | 3 | 4 | 5 | 6 | 7;
+    orientation?: number;
+    connections?: Record<string, Connection>;
+    control_behavior?: ControlBehavior;
+    items?: ItemRequest;
+    recipe?: string;
+    bar?: number;
+    inventory?: Inventory;
+    infinity_settings?: InfinitySettings;
+    type?: "input" | "output" | "item";
+    input_priority?: "left" | "right";
+    output_priority?: "left" | "right";
+    filter?: string;
+    filters?: ItemFilter[];
+    filter_mode?: "whitelist" | "blacklist";
+    override_stack_size?: number;
+    drop_position?: Position;
+    pickup_position?: Position;
+    request_filters?: LogisticFilter[];
+    request_from_buffers?: boolean;
+    parameters?: SpeakerParameter;
+    alert_parameters?: SpeakerAlertParameter;
+    auto_launch?: boolean;
+    variation?: number;
+    color?: Color;
+    station?: string;
+    switch_state?: boolean;
+    manual_trains_limit?: number;
+    neighbours?: number[];
+}
+
+export type Connection = ConnectionPoint | ConnectionData[];
+
+export interface ConnectionPoint {
+    red?: ConnectionData[] | null;
+    green?: ConnectionData[] | null;
+}
+
+export interface ConnectionData {
+    entity_id: number;
+    circuit_id?: number | null;
+    wire_id?: number | null;
+}
+
+export type ItemRequest = Record<string, number> | ItemRequestVerbose[];
+
+export interface ItemRequestVerbose {
+    item: string;
+    count: number;
+}
+
+export interface ControlBehavior {
+    connect_to_logistic_network?: boolean;
+    arithmetic_conditions?: ArithmeticConditions;
+    decider_conditions?: DeciderConditions;
+    logistic_condition?: LogisticCondition;
+    filters?: ControlFilter[];
+    is_on?: boolean;
+    use_colors?: boolean;
+    circuit_condition?: CircuitCondition;
+    /** 0 for roboports, 1 to set requests, 3 for none */
+    circuit_mode_of_operation?: 0 | 1 | 3;
+    circuit_enable_disable?: boolean;
+    /** 0 to pulse, 1 to hold */
+    circuit_contents_read_mode?: 0 | 1;
+    circuit_hand_read_mode?: 0 | 1;
+    circuit_read_hand_contents?: boolean;
+    circuit_set_stack_size?: boolean;
+    stack_control_input_signal?: SimpleEntity;
+    circuit_parameters?: SpeakerCircuitParameters;
+    output_signal?: SimpleEntity;
+    read_from_train?: boolean;
+    read_stopped_train?: boolean;
+    read_trains_count?: boolean;
+    set_trains_limit?: boolean;
+    send_to_train?: boolean;
+    train_stopped_signal?: SimpleEntity;
+    trains_count_signal?: SimpleEntity;
+    trains_limit_signal?: SimpleEntity;
+    read_logistics?: boolean;
+    read_robot_stats?: boolean;
+    available_construction_output_signal?: SimpleEntity;
+    available_logistic_output_signal?: SimpleEntity;
+    total_construction_output_signal?: SimpleEntity;
+    total_logistic_output_signal?: SimpleEntity;
+    circuit_open_gate?: boolean;
+    circuit_read_sensor?: boolean;
+    circuit_close_signal?: boolean;
+    circuit_read_signal?: boolean;
+}
+
+export interface ArithmeticConditions {
+    first_constant?: number;
+    first_signal?: SignalID;
+    second_constant?: number;
+    second_signal?: SignalID;
+    operation: "+" | "-" | "*" | "/" | "%" | "^" | "<<" | ">>" | "AND" | "OR" | "XOR";
+    output_signal?: SignalID;
+}
+
+export interface DeciderConditions {
+    first_signal?: SignalID;
+    second_signal?: SignalID;
+    constant?: number;
+    comparator: ">" | "<" | "≥" | "≤" | "=" | "≠";
+    output_signal?: SignalID;
+    copy_count_from_input?: boolean;
+}
+
+export interface LogisticCondition {
+    first_signal?: SignalID;
+    second_signal?: SignalID;
+    constant?: number;
+    comparator: string;
+}
+
+export interface CircuitCondition {
+    comparator: string;
+    constant?: number | null;
+    first_signal?: SimpleEntity | null;
+    second_signal?: SimpleEntity | null;
+}
+
+export interface SpeakerCircuitParameters {
+    instrument_id: number;
+    note_id: number;
+    signal_value_is_pitch: boolean;
+}
+
+export interface Inventory {
+    filters: ItemFilter[];
+    bar?: number | null;
+}
+
+export interface Schedule {
+    schedule: ScheduleRecord[];
+    locomotives: number[];
+}
+
+export interface ScheduleRecord {
+    station: string;
+    wait_conditions?: WaitCondition[] | null;
+}
+
+export interface WaitCondition {
+    type:
+        | "time"
+        | "inactivity"
+        | "full"
+        | "empty"
+        | "item_count"
+        | "circuit"
+        | "robots_inactive"
+        | "fluid_count"
+        | "passenger_present"
+        | "passenger_not_present";
+    compare_type: "and" | "or";
+    ticks?: number | null;
+    condition?: CircuitCondition | null;
+}
+
+export interface ItemFilter {
+    name: string;
+    index: number;
+}
+
+export interface InfinitySettings {
+    remove_unfiltered_items: boolean;
+    filters?: InfinityFilter[] | null;
+}
+
+export interface InfinityFilter {
+    name: string;
+    count: number;
+    mode: "at-least" | "at-most" | "exactly";
+    index: number;
+}
+
+export interface LogisticFilter {
+    name: string;
+    index: number;
+    count: number;
+}
+
+export interface ControlFilter {
+    signal: SignalID;
+    index: number;
+    count: number;
+}
+
+export interface SpeakerParameter {
+    playback_volume: number;
+    playback_globally: boolean;
+    allow_polyphony: boolean;
+}
+
+export interface SpeakerAlertParameter {
+    show_alert: boolean;
+    show_on_map: boolean;
+    icon_signal_id?: SignalID | null;
+    alert_message: string;
+}
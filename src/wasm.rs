@@ -0,0 +1,67 @@
+//! Bindings for JavaScript, through wasm-bindgen.
+//!
+//! Available with the `wasm` feature, and meant to be built for
+//! `wasm32-unknown-unknown`, for example with
+//! `wasm-pack build --features wasm`. Nothing here touches a filesystem or
+//! starts a thread, so the package runs in browsers as well as in node.
+//!
+//! Containers cross into JavaScript as plain objects with exactly the shape
+//! of their json: maps become objects, and absent values are left out.
+//! TypeScript declarations for that shape are included in the package, so
+//! `decode` returns a `Container` and `encode` accepts one.
+
+use crate::{BlueprintCodec, Container, Error, Result};
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+// checked against the json schema in tests/schema.rs
+#[wasm_bindgen(typescript_custom_section)]
+const TYPES: &'static str = include_str!("wasm.d.ts");
+
+#[wasm_bindgen]
+extern "C" {
+    /// A [`Container`] as a JavaScript object.
+    #[wasm_bindgen(typescript_type = "Container")]
+    #[derive(Debug, Clone)]
+    pub type JsContainer;
+}
+
+/// convert the container to a JavaScript object of the same shape as its
+/// json
+pub fn to_js(container: &Container) -> Result<JsValue> {
+    container
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(|err| Error::JsValue {
+            path: String::new(),
+            message: err.to_string(),
+        })
+}
+
+/// read a container from a JavaScript object of the same shape as its json
+pub fn from_js(value: JsValue) -> Result<Container> {
+    serde_path_to_error::deserialize(serde_wasm_bindgen::Deserializer::from(value)).map_err(|err| {
+        Error::JsValue {
+            path: err.path().to_string(),
+            message: err.into_inner().to_string(),
+        }
+    })
+}
+
+/// decode a blueprint string into a `Container` object
+///
+/// Throws an `Error` whose message describes what was wrong with the
+/// string.
+#[wasm_bindgen]
+pub fn decode(blueprint: &str) -> std::result::Result<JsContainer, JsError> {
+    let container = BlueprintCodec::decode_string(blueprint)?;
+    Ok(to_js(&container)?.unchecked_into())
+}
+
+/// encode a `Container` object as a blueprint string
+///
+/// Throws an `Error` if the object is not a valid container.
+#[wasm_bindgen]
+pub fn encode(container: JsContainer) -> std::result::Result<String, JsError> {
+    let container = from_js(container.into())?;
+    Ok(BlueprintCodec::encode_string(&container)?)
+}
@@ -1,16 +1,74 @@
+use crate::stage::{self, Stage};
 use std::io::{Read, Result};
 
 /// Remove all ascii whitespace from an incoming stream.
+///
+/// The remover keeps track of where it removed whitespace, so that offsets
+/// into its output can be mapped back to offsets into its input.
 pub struct WhitespaceRemover<R> {
     inner: R,
+    /// number of bytes read from `inner`
+    position: u64,
+    /// number of bytes emitted
+    emitted: u64,
+    /// `(emitted, removed)` pairs: the output byte at offset `emitted`, and
+    /// every byte after it, was preceded by a total of `removed` whitespace
+    /// bytes in the input.
+    removals: Vec<(u64, u64)>,
 }
 
-impl<R> WhitespaceRemover<R>
-where
-    R: Read,
-{
+impl<R> WhitespaceRemover<R> {
     pub fn new(inner: R) -> WhitespaceRemover<R> {
-        WhitespaceRemover { inner }
+        WhitespaceRemover {
+            inner,
+            position: 0,
+            emitted: 0,
+            removals: Vec::new(),
+        }
+    }
+
+    /// number of bytes read from the underlying reader so far
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// number of non-whitespace bytes emitted so far
+    pub fn emitted(&self) -> u64 {
+        self.emitted
+    }
+
+    /// map an offset into the output of this reader to the offset of the
+    /// same byte in its input
+    pub fn input_offset(&self, output_offset: u64) -> u64 {
+        let idx = self
+            .removals
+            .partition_point(|&(emitted, _)| emitted <= output_offset);
+        let removed = idx.checked_sub(1).map_or(0, |idx| self.removals[idx].1);
+        output_offset + removed
+    }
+
+    /// compact the whitespace out of freshly read data, returning the number
+    /// of bytes kept
+    fn strip(&mut self, data: &mut [u8]) -> usize {
+        self.position += data.len() as u64;
+        let mut kept = 0;
+        for idx in 0..data.len() {
+            let byte = data[idx];
+            if byte.is_ascii_whitespace() {
+                match self.removals.last_mut() {
+                    Some((emitted, removed)) if *emitted == self.emitted => *removed += 1,
+                    last => {
+                        let removed = last.map_or(0, |&mut (_, removed)| removed) + 1;
+                        self.removals.push((self.emitted, removed));
+                    }
+                }
+            } else {
+                data[kept] = byte;
+                kept += 1;
+                self.emitted += 1;
+            }
+        }
+        kept
     }
 }
 
@@ -19,26 +77,61 @@ where
     R: Read,
 {
     fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
-        let mut my_buf = vec![0; buf.len()];
-
         loop {
-            let n = self.inner.read(&mut my_buf)?;
+            let n = self
+                .inner
+                .read(buf)
+                .map_err(|e| stage::tag(Stage::Input, self.position, e))?;
             if n == 0 {
                 // the underlying reader is done
                 return Ok(0);
             }
 
-            my_buf.truncate(n);
-            my_buf.retain(|d| !d.is_ascii_whitespace());
+            // ensure we got at least a byte to return; otherwise reread
+            let kept = self.strip(&mut buf[..n]);
+            if kept > 0 {
+                return Ok(kept);
+            }
+        }
+    }
+}
 
-            // ensure we got at least a byte to return
-            if !my_buf.is_empty() {
-                buf[..my_buf.len()].copy_from_slice(&my_buf);
-                return Ok(my_buf.len());
+#[cfg(feature = "async")]
+impl<R> tokio::io::AsyncRead for WhitespaceRemover<R>
+where
+    R: tokio::io::AsyncRead + Unpin,
+{
+    fn poll_read(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> std::task::Poll<Result<()>> {
+        use std::task::Poll;
+
+        let this = self.get_mut();
+        loop {
+            let start = buf.filled().len();
+            match std::pin::Pin::new(&mut this.inner).poll_read(cx, buf) {
+                Poll::Ready(Ok(())) => {}
+                Poll::Ready(Err(e)) => {
+                    return Poll::Ready(Err(stage::tag(Stage::Input, this.position, e)))
+                }
+                Poll::Pending => return Poll::Pending,
             }
 
-            // otherwise reset and reread
-            my_buf.resize(buf.len(), 0);
+            let fresh = &mut buf.filled_mut()[start..];
+            if fresh.is_empty() {
+                // the underlying reader is done
+                return Poll::Ready(Ok(()));
+            }
+
+            let kept = this.strip(fresh);
+            buf.set_filled(start + kept);
+
+            // ensure we got at least a byte to return; otherwise reread
+            if kept > 0 {
+                return Poll::Ready(Ok(()));
+            }
         }
     }
 }
@@ -78,4 +171,21 @@ mod tests {
 
         assert_eq!(stripped, "ab");
     }
+
+    #[test]
+    fn input_offsets() {
+        const TEST: &str = "  ab\tc\n\n de  f";
+
+        let mut remover = super::WhitespaceRemover::new(TEST.as_bytes());
+        let mut stripped = String::new();
+        remover.read_to_string(&mut stripped).unwrap();
+        assert_eq!(stripped, "abcdef");
+
+        for (output_offset, ch) in stripped.char_indices() {
+            let input_offset = remover.input_offset(output_offset as u64) as usize;
+            assert_eq!(TEST[input_offset..].chars().next(), Some(ch));
+        }
+        assert_eq!(remover.position(), TEST.len() as u64);
+        assert_eq!(remover.emitted(), 6);
+    }
 }
@@ -0,0 +1,138 @@
+#![cfg(feature = "async")]
+
+use factorio_blueprint::{
+    encode_options::EncodeOptions, limits::DecodeLimits, BlueprintCodec, Container, Error,
+};
+use std::io::Write;
+
+mod common;
+use common::examples;
+
+#[tokio::test]
+async fn can_decode_examples_async() {
+    for example in examples() {
+        let file = tokio::fs::File::open(&example)
+            .await
+            .expect("can open example file");
+        let container = Container::decode_async(file).await.expect("can decode");
+
+        let blueprint = std::fs::read_to_string(&example).expect("can read from file");
+        assert_eq!(
+            container,
+            BlueprintCodec::decode_string(&blueprint).unwrap()
+        );
+    }
+}
+
+#[tokio::test]
+async fn async_encoding_matches_blocking() {
+    for example in examples() {
+        let blueprint = std::fs::read_to_string(example).expect("can read from file");
+        let container = BlueprintCodec::decode_string(&blueprint).unwrap();
+
+        let encoded = BlueprintCodec::encode_string_async(&container)
+            .await
+            .expect("can encode");
+        assert_eq!(encoded, BlueprintCodec::encode_string(&container).unwrap());
+    }
+}
+
+#[tokio::test]
+async fn roundtrip_async() {
+    for example in examples() {
+        let blueprint = std::fs::read_to_string(example).expect("can read from file");
+        let container = BlueprintCodec::decode_string(&blueprint).unwrap();
+
+        let mut encoded = Vec::new();
+        container
+            .encode_async(&mut encoded)
+            .await
+            .expect("can encode");
+        let decoded = Container::decode_async(encoded.as_slice())
+            .await
+            .expect("can decode");
+        assert_eq!(container, decoded);
+    }
+}
+
+#[tokio::test]
+async fn async_encoding_with_options_matches_blocking() {
+    let blueprint = std::fs::read_to_string(examples().next().unwrap()).unwrap();
+    let container = BlueprintCodec::decode_string(&blueprint).unwrap();
+    let options = [
+        EncodeOptions::fast(),
+        EncodeOptions {
+            line_width: Some(76),
+            canonical: true,
+            ..EncodeOptions::default()
+        },
+    ];
+    for options in &options {
+        let mut blocking = Vec::new();
+        BlueprintCodec::encode_with(&mut blocking, &container, options).unwrap();
+        let mut encoded = Vec::new();
+        container
+            .encode_async_with(&mut encoded, options)
+            .await
+            .expect("can encode");
+        assert_eq!(encoded, blocking);
+    }
+
+    let options = EncodeOptions {
+        compression_level: 42,
+        ..EncodeOptions::default()
+    };
+    let result = BlueprintCodec::encode_async_with(Vec::new(), &container, &options).await;
+    assert!(matches!(result, Err(Error::CompressionLevel { level: 42 })));
+}
+
+#[tokio::test]
+async fn limits_apply_async() {
+    // a few kilobytes of blueprint string which expands to 64 MiB
+    let mut bomb = Vec::new();
+    BlueprintCodec::encode_writer(&mut bomb, |mut writer| {
+        writer.write_all(br#"{"blueprint":{"item":"blueprint","version":0,"label":""#)?;
+        let spaces = vec![b' '; 1024 * 1024];
+        for _ in 0..64 {
+            writer.write_all(&spaces)?;
+        }
+        writer.write_all(br#""}}"#)
+    })
+    .unwrap();
+    let limits = DecodeLimits {
+        max_decompressed_bytes: 1024 * 1024,
+        ..DecodeLimits::default()
+    };
+    let err = BlueprintCodec::decode_async_with_limits(bomb.as_slice(), &limits)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, Error::DecompressedTooLarge { limit } if limit == 1024 * 1024),
+        "{:?}",
+        err
+    );
+
+    let limits = DecodeLimits {
+        max_input_bytes: 100,
+        ..DecodeLimits::default()
+    };
+    let err = BlueprintCodec::decode_async_with_limits(bomb.as_slice(), &limits)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, Error::InputTooLarge { limit: 100 }),
+        "{:?}",
+        err
+    );
+
+    let limits = DecodeLimits {
+        max_entities: 0,
+        ..DecodeLimits::default()
+    };
+    for example in examples() {
+        let blueprint = std::fs::read_to_string(example).unwrap();
+        let blocking = BlueprintCodec::decode_with_limits(blueprint.as_bytes(), &limits);
+        let result = BlueprintCodec::decode_async_with_limits(blueprint.as_bytes(), &limits).await;
+        assert_eq!(format!("{:?}", result), format!("{:?}", blocking));
+    }
+}
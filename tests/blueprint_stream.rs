@@ -0,0 +1,95 @@
+use factorio_blueprint::{
+    objects::{Blueprint, Entity, Position, Tile},
+    BlueprintCodec, Container, Error,
+};
+use noisy_float::types::r64;
+
+mod common;
+use common::examples;
+
+#[test]
+fn streamed_blueprints_match_examples() {
+    for example in examples() {
+        let string = std::fs::read_to_string(&example).unwrap();
+        let blueprint = match BlueprintCodec::decode_string(&string).unwrap() {
+            Container::Blueprint(blueprint) => blueprint,
+            _ => continue,
+        };
+        let header = Blueprint {
+            entities: Vec::new(),
+            tiles: Vec::new(),
+            schedules: Vec::new(),
+            ..blueprint.clone()
+        };
+
+        let mut out = Vec::new();
+        BlueprintCodec::encode_blueprint_stream(&mut out, &header, |stream| {
+            stream.entities(blueprint.entities.iter().cloned())?;
+            stream.tiles(blueprint.tiles.iter().cloned())?;
+            stream.schedules(blueprint.schedules.iter().cloned())
+        })
+        .unwrap();
+        assert_eq!(
+            BlueprintCodec::decode(out.as_slice()).unwrap(),
+            Container::Blueprint(blueprint),
+            "{}",
+            example.display()
+        );
+    }
+}
+
+#[test]
+fn header_contents_come_first() {
+    let entity = |entity_number: usize| -> Entity {
+        serde_json::from_value(serde_json::json!({
+            "entity_number": entity_number,
+            "name": "stone-wall",
+            "position": {"x": entity_number, "y": 0},
+        }))
+        .unwrap()
+    };
+    let header = Blueprint {
+        entities: vec![entity(1)],
+        ..Default::default()
+    };
+    let mut out = Vec::new();
+    BlueprintCodec::encode_blueprint_stream(&mut out, &header, |stream| stream.entity(&entity(2)))
+        .unwrap();
+    match BlueprintCodec::decode(out.as_slice()).unwrap() {
+        Container::Blueprint(blueprint) => {
+            assert_eq!(blueprint.entities, vec![entity(1), entity(2)])
+        }
+        other => panic!("unexpected container {:?}", other),
+    }
+}
+
+#[test]
+fn sections_must_be_in_order() {
+    let mut out = Vec::new();
+    let result =
+        BlueprintCodec::encode_blueprint_stream(&mut out, &Blueprint::default(), |stream| {
+            let position = Position {
+                x: r64(0.0),
+                y: r64(0.0),
+            };
+            stream.tile(&Tile {
+                name: "stone-path".into(),
+                position,
+            })?;
+            stream.entity(
+                &serde_json::from_value(serde_json::json!({
+                    "entity_number": 1,
+                    "name": "stone-wall",
+                    "position": {"x": 0, "y": 0},
+                }))
+                .unwrap(),
+            )
+        });
+    assert!(matches!(
+        result,
+        Err(Error::StreamOrder {
+            found: "entities",
+            after: "tiles"
+        })
+    ));
+}
@@ -0,0 +1,84 @@
+use factorio_blueprint::{encode_options::EncodeOptions, BlueprintCodec, Container};
+use noisy_float::types::r64;
+
+mod common;
+use common::examples;
+
+fn containers() -> impl Iterator<Item = Container> {
+    examples().map(|example| {
+        BlueprintCodec::decode_string(&std::fs::read_to_string(example).unwrap()).unwrap()
+    })
+}
+
+fn blueprints(container: &mut Container) -> Vec<&mut factorio_blueprint::objects::Blueprint> {
+    match container {
+        Container::Blueprint(blueprint) => vec![blueprint],
+        Container::BlueprintBook(book) => book
+            .blueprints
+            .iter_mut()
+            .flat_map(|page| blueprints(&mut page.item))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[test]
+fn encoding_is_deterministic() {
+    for example in examples() {
+        let string = std::fs::read_to_string(example).unwrap();
+        let first = BlueprintCodec::decode_string(&string).unwrap();
+        let second = BlueprintCodec::decode_string(&string).unwrap();
+        assert_eq!(
+            BlueprintCodec::encode_string(&first).unwrap(),
+            BlueprintCodec::encode_string(&second).unwrap(),
+        );
+    }
+}
+
+#[test]
+fn negative_zero_encodes_as_zero() {
+    for mut container in containers() {
+        let expect = BlueprintCodec::encode_string(&container).unwrap();
+        for blueprint in blueprints(&mut container) {
+            for entity in &mut blueprint.entities {
+                if entity.position.x == r64(0.0) {
+                    entity.position.x = r64(-0.0);
+                }
+            }
+        }
+        assert_eq!(BlueprintCodec::encode_string(&container).unwrap(), expect);
+    }
+}
+
+#[test]
+fn canonical_ignores_order() {
+    for container in containers() {
+        let expect =
+            BlueprintCodec::encode_string_with(&container, &EncodeOptions::canonical()).unwrap();
+
+        let mut shuffled = container.clone();
+        for blueprint in blueprints(&mut shuffled) {
+            blueprint.entities.reverse();
+            blueprint.tiles.reverse();
+            blueprint.icons.reverse();
+        }
+        if let Container::BlueprintBook(book) = &mut shuffled {
+            book.blueprints.reverse();
+        }
+        assert_eq!(
+            BlueprintCodec::encode_string_with(&shuffled, &EncodeOptions::canonical()).unwrap(),
+            expect,
+        );
+    }
+}
+
+#[test]
+fn canonical_roundtrips() {
+    for container in containers() {
+        let encoded =
+            BlueprintCodec::encode_string_with(&container, &EncodeOptions::canonical()).unwrap();
+        let mut canonical = container.clone();
+        canonical.canonicalize();
+        assert_eq!(BlueprintCodec::decode_string(&encoded).unwrap(), canonical);
+    }
+}
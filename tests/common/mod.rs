@@ -0,0 +1,14 @@
+use std::path::PathBuf;
+
+pub fn examples() -> impl Iterator<Item = PathBuf> {
+    let mut examples = std::env::current_exe().expect("can find test executable");
+    // target/debug/1/executable
+    for _ in 0..4 {
+        examples.pop();
+    }
+    examples.push("tests/examples");
+
+    std::fs::read_dir(examples)
+        .expect("should find examples dir")
+        .map(|maybe_example| maybe_example.expect("should find file").path())
+}
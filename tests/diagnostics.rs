@@ -0,0 +1,139 @@
+use factorio_blueprint::{BlueprintCodec, Error, Stage};
+use std::io::{Read, Write};
+
+/// encode arbitrary json as a blueprint string
+fn encode_json(json: &str) -> String {
+    let mut out = Vec::new();
+    BlueprintCodec::encode_writer(&mut out, |mut writer| writer.write_all(json.as_bytes()))
+        .unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+const VALID: &str = r#"{"blueprint":{"item":"blueprint","version":281474976710656,"entities":[{"entity_number":1,"name":"transport-belt","position":{"x":2.5,"y":0.5}}]}}"#;
+
+#[test]
+fn valid_json_decodes() {
+    BlueprintCodec::decode_string(&encode_json(VALID)).unwrap();
+}
+
+#[test]
+fn no_data() {
+    for input in &["", "  \n\t "] {
+        let err = BlueprintCodec::decode_string(input).unwrap_err();
+        assert!(matches!(err, Error::NoData), "{:?}", err);
+    }
+}
+
+#[test]
+fn unknown_version_names_byte_and_offset() {
+    let blueprint = format!("\n  1{}", &encode_json(VALID)[1..]);
+    let err = BlueprintCodec::decode_string(&blueprint).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            Error::UnknownVersion {
+                found: b'1',
+                offset: 3
+            }
+        ),
+        "{:?}",
+        err
+    );
+    assert_eq!(err.stage(), Some(Stage::Version));
+    assert_eq!(
+        err.to_string(),
+        "unexpected blueprint string version byte '1' at byte 3"
+    );
+}
+
+#[test]
+fn base64_offset_accounts_for_whitespace() {
+    let blueprint = encode_json(VALID);
+    // wrap the string, then corrupt the 30th base64 character
+    let mut wrapped = String::new();
+    for (idx, ch) in blueprint.chars().enumerate() {
+        if idx > 0 && idx % 10 == 0 {
+            wrapped.push_str("\r\n");
+        }
+        wrapped.push(if idx == 30 { '!' } else { ch });
+    }
+
+    let err = BlueprintCodec::decode_string(&wrapped).unwrap_err();
+    assert_eq!(err.stage(), Some(Stage::Base64));
+    match err {
+        Error::Base64 { offset, .. } => {
+            assert_eq!(&wrapped[offset as usize..offset as usize + 1], "!")
+        }
+        err => panic!("unexpected error: {:?}", err),
+    }
+}
+
+#[test]
+fn corrupt_zlib_stream() {
+    let blueprint = format!("0{}", base64::encode("this is not a zlib stream"));
+    let err = BlueprintCodec::decode_string(&blueprint).unwrap_err();
+    assert!(matches!(err, Error::Zlib { .. }), "{:?}", err);
+    assert_eq!(err.stage(), Some(Stage::Zlib));
+}
+
+#[test]
+fn input_errors_report_offset() {
+    /// yields some data, then fails
+    struct Failing<'a>(&'a [u8]);
+
+    impl Read for Failing<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.0.is_empty() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::ConnectionReset,
+                    "connection reset",
+                ));
+            }
+            let n = self.0.len().min(buf.len()).min(7);
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    let blueprint = encode_json(VALID);
+    let partial = &blueprint.as_bytes()[..40];
+    let err = BlueprintCodec::decode(Failing(partial)).unwrap_err();
+    assert!(matches!(err, Error::Input { offset: 40, .. }), "{:?}", err);
+    assert_eq!(err.stage(), Some(Stage::Input));
+}
+
+#[test]
+fn json_errors_have_full_path() {
+    let json = r#"{"blueprint_book":{"item":"blueprint-book","active_index":0,"version":0,"blueprints":[
+        {"index":0,"blueprint":{"item":"blueprint","version":0}},
+        {"blueprint":{"item":"blueprint","version":0,"entities":[
+            {"entity_number":1,"name":"inserter","position":{"x":0,"y":0}},
+            {"entity_number":2,"name":"inserter","position":{"x":1,"y":0},
+             "control_behavior":{"circuit_mode_of_operation":7}}
+        ]},"index":1}
+    ]}}"#;
+    let err = BlueprintCodec::decode_string(&encode_json(json)).unwrap_err();
+    assert_eq!(err.stage(), Some(Stage::Json));
+    match err {
+        Error::Json { path, .. } => assert_eq!(
+            path,
+            "blueprint_book.blueprints[1].blueprint.entities[1].control_behavior.circuit_mode_of_operation"
+        ),
+        err => panic!("unexpected error: {:?}", err),
+    }
+}
+
+#[test]
+fn untagged_enums_explain_mismatch() {
+    let json = r#"{"blueprint":{"item":"blueprint","version":0,"entities":[
+        {"entity_number":1,"name":"assembling-machine-2","position":{"x":0,"y":0},"items":5}
+    ]}}"#;
+    let err = BlueprintCodec::decode_string(&encode_json(json)).unwrap_err();
+    let message = err.to_string();
+    assert!(
+        message.starts_with("json error at `blueprint.entities[0].items`: item request"),
+        "{}",
+        message
+    );
+}
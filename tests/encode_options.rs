@@ -0,0 +1,106 @@
+use factorio_blueprint::{encode_options::EncodeOptions, BlueprintCodec, Container, Error};
+
+mod common;
+use common::examples;
+
+fn containers() -> impl Iterator<Item = Container> {
+    examples().map(|example| {
+        BlueprintCodec::decode_string(&std::fs::read_to_string(example).unwrap()).unwrap()
+    })
+}
+
+#[test]
+fn default_options_match_encode() {
+    for container in containers() {
+        assert_eq!(
+            BlueprintCodec::encode_string_with(&container, &EncodeOptions::default()).unwrap(),
+            BlueprintCodec::encode_string(&container).unwrap(),
+        );
+    }
+}
+
+#[test]
+fn presets_roundtrip() {
+    for container in containers() {
+        for options in &[EncodeOptions::fast(), EncodeOptions::balanced()] {
+            let encoded = BlueprintCodec::encode_string_with(&container, options).unwrap();
+            assert_eq!(BlueprintCodec::decode_string(&encoded).unwrap(), container);
+        }
+    }
+}
+
+#[test]
+fn compression_level_affects_size() {
+    let container = containers().next().unwrap();
+    let stored = EncodeOptions {
+        compression_level: 0,
+        ..EncodeOptions::default()
+    };
+    assert!(
+        BlueprintCodec::encode_string_with(&container, &stored)
+            .unwrap()
+            .len()
+            > BlueprintCodec::encode_string(&container).unwrap().len()
+    );
+}
+
+#[test]
+fn line_wrapping() {
+    let options = EncodeOptions {
+        line_width: Some(76),
+        ..EncodeOptions::default()
+    };
+    for container in containers() {
+        let mut encoded = Vec::new();
+        container.encode_with(&mut encoded, &options).unwrap();
+        let encoded = String::from_utf8(encoded).unwrap();
+
+        let lines: Vec<_> = encoded.split('\n').collect();
+        assert!(lines.iter().all(|line| line.len() <= 76));
+        assert!(lines[..lines.len() - 1].iter().all(|line| line.len() == 76));
+        assert_eq!(
+            lines.concat(),
+            BlueprintCodec::encode_string(&container).unwrap()
+        );
+        assert_eq!(BlueprintCodec::decode_string(&encoded).unwrap(), container);
+    }
+}
+
+#[test]
+fn version_byte() {
+    let container = containers().next().unwrap();
+    let options = EncodeOptions {
+        version: '1',
+        ..EncodeOptions::default()
+    };
+    let encoded = BlueprintCodec::encode_string_with(&container, &options).unwrap();
+    assert!(encoded.starts_with('1'));
+    let err = BlueprintCodec::decode_string(&encoded).unwrap_err();
+    assert!(
+        matches!(err, Error::UnknownVersion { found: b'1', .. }),
+        "{:?}",
+        err
+    );
+}
+
+#[test]
+fn invalid_options_are_rejected() {
+    let container = containers().next().unwrap();
+    let options = EncodeOptions {
+        compression_level: 42,
+        ..EncodeOptions::default()
+    };
+    let err = BlueprintCodec::encode_string_with(&container, &options).unwrap_err();
+    assert!(
+        matches!(err, Error::CompressionLevel { level: 42 }),
+        "{:?}",
+        err
+    );
+
+    let options = EncodeOptions {
+        version: 'é',
+        ..EncodeOptions::default()
+    };
+    let err = BlueprintCodec::encode_string_with(&container, &options).unwrap_err();
+    assert!(matches!(err, Error::VersionByte('é')), "{:?}", err);
+}
@@ -0,0 +1,43 @@
+use factorio_blueprint::objects::Entity;
+use serde_json::json;
+
+#[test]
+fn plain_entities_have_no_extra() {
+    let json = json!({"entity_number": 1, "name": "stone-wall", "position": {"x": 0.5, "y": 0.5}});
+    let entity: Entity = serde_json::from_value(json.clone()).unwrap();
+    assert!(entity.extra().is_none());
+    assert_eq!(serde_json::to_value(&entity).unwrap(), json);
+}
+
+#[test]
+fn rare_settings_roundtrip_through_extra() {
+    let json = json!({
+        "entity_number": 1,
+        "name": "train-stop",
+        "position": {"x": 1, "y": 1},
+        "direction": 4,
+        "color": {"r": 1, "g": 0, "b": 0, "a": 0.5},
+        "station": "Iron pickup",
+        "manual_trains_limit": 2,
+    });
+    let entity: Entity = serde_json::from_value(json.clone()).unwrap();
+    let extra = entity.extra().unwrap();
+    assert_eq!(extra.station.as_deref(), Some("Iron pickup"));
+    assert_eq!(extra.manual_trains_limit, Some(2));
+    assert_eq!(serde_json::to_value(&entity).unwrap(), json);
+}
+
+#[test]
+fn empty_extra_is_the_same_as_none() {
+    let json = json!({"entity_number": 1, "name": "stone-wall", "position": {"x": 0.5, "y": 0.5}});
+    let entity: Entity = serde_json::from_value(json.clone()).unwrap();
+    let mut touched = entity.clone();
+    touched.extra_mut();
+    assert!(touched.extra.is_some());
+    assert!(touched.extra().is_none());
+    assert_eq!(touched, entity);
+    assert_eq!(serde_json::to_value(&touched).unwrap(), json);
+
+    touched.extra_mut().station = Some("Iron pickup".into());
+    assert_ne!(touched, entity);
+}
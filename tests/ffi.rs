@@ -0,0 +1,184 @@
+#![cfg(feature = "ffi")]
+
+use factorio_blueprint::{ffi::*, BlueprintCodec, Container};
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::ptr;
+
+mod common;
+use common::examples;
+
+fn decode(blueprint: &str) -> Result<*mut Container, FbStatus> {
+    let mut container = ptr::null_mut();
+    match unsafe { fb_decode(blueprint.as_ptr().cast(), blueprint.len(), &mut container) } {
+        FbStatus::Ok => Ok(container),
+        status => Err(status),
+    }
+}
+
+fn str_of<'a>(s: FbStr) -> Option<&'a str> {
+    if s.ptr.is_null() {
+        return None;
+    }
+    let bytes = unsafe { std::slice::from_raw_parts(s.ptr.cast::<u8>(), s.len) };
+    Some(std::str::from_utf8(bytes).unwrap())
+}
+
+/// take ownership of a string returned by the library
+fn take_string(s: *mut c_char) -> String {
+    let owned = unsafe { CStr::from_ptr(s) }.to_str().unwrap().to_string();
+    unsafe { fb_string_free(s) };
+    owned
+}
+
+fn last_error() -> String {
+    unsafe { CStr::from_ptr(fb_last_error()) }
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+/// count the entities of the container, checking each against `expected`
+unsafe fn walk(container: *const Container, expected: &Container) -> usize {
+    match expected {
+        Container::BlueprintBook(book) => {
+            assert_eq!(fb_container_kind(container), FbContainerKind::BlueprintBook);
+            assert!(fb_container_blueprint(container).is_null());
+            let handle = fb_container_book(container);
+            assert_eq!(fb_book_page_count(handle), book.blueprints.len());
+            assert_eq!(fb_book_active_index(handle), book.active_index);
+            assert!(fb_book_page(handle, book.blueprints.len()).is_null());
+            assert_eq!(
+                fb_book_page_index(handle, book.blueprints.len()),
+                usize::MAX
+            );
+            book.blueprints
+                .iter()
+                .enumerate()
+                .map(|(n, page)| {
+                    assert_eq!(fb_book_page_index(handle, n), page.index);
+                    walk(fb_book_page(handle, n), &page.item)
+                })
+                .sum()
+        }
+        Container::Blueprint(blueprint) => {
+            assert_eq!(fb_container_kind(container), FbContainerKind::Blueprint);
+            assert!(fb_container_book(container).is_null());
+            let handle = fb_container_blueprint(container);
+            assert_eq!(
+                fb_container_label(container).ptr.is_null(),
+                blueprint.label.is_none()
+            );
+            assert_eq!(
+                str_of(fb_container_label(container)),
+                blueprint.label.as_deref()
+            );
+            assert_eq!(fb_container_version(container), blueprint.version);
+            assert_eq!(
+                str_of(fb_blueprint_description(handle)),
+                blueprint.description.as_deref()
+            );
+            assert_eq!(fb_blueprint_entity_count(handle), blueprint.entities.len());
+            for (n, entity) in blueprint.entities.iter().enumerate() {
+                let e = fb_blueprint_entity(handle, n);
+                assert_eq!(fb_entity_number(e), entity.entity_number.get());
+                assert_eq!(str_of(fb_entity_name(e)), Some(entity.name.as_str()));
+                assert_eq!(fb_entity_position(e).x, entity.position.x.raw());
+                assert_eq!(fb_entity_position(e).y, entity.position.y.raw());
+                assert_eq!(
+                    fb_entity_direction(e),
+                    entity.direction.clone().unwrap_or_default() as u8
+                );
+                assert_eq!(str_of(fb_entity_recipe(e)), entity.recipe.as_deref());
+            }
+            assert!(fb_blueprint_entity(handle, blueprint.entities.len()).is_null());
+            assert_eq!(fb_blueprint_tile_count(handle), blueprint.tiles.len());
+            for (n, tile) in blueprint.tiles.iter().enumerate() {
+                let t = fb_blueprint_tile(handle, n);
+                assert_eq!(str_of(fb_tile_name(t)), Some(tile.name.as_str()));
+                assert_eq!(fb_tile_position(t).x, tile.position.x.raw());
+            }
+            blueprint.entities.len()
+        }
+        Container::DeconstructionPlanner(_) => {
+            assert_eq!(
+                fb_container_kind(container),
+                FbContainerKind::DeconstructionPlanner
+            );
+            0
+        }
+        Container::UpgradePlanner(_) => {
+            assert_eq!(
+                fb_container_kind(container),
+                FbContainerKind::UpgradePlanner
+            );
+            0
+        }
+    }
+}
+
+#[test]
+fn examples_can_be_walked_and_encoded() {
+    for example in examples() {
+        let blueprint = std::fs::read_to_string(&example).unwrap();
+        let expected = BlueprintCodec::decode_string(&blueprint).unwrap();
+        let container = decode(&blueprint).unwrap();
+        unsafe {
+            walk(container, &expected);
+
+            let mut encoded = ptr::null_mut();
+            assert_eq!(fb_encode(container, &mut encoded), FbStatus::Ok);
+            assert_eq!(
+                take_string(encoded),
+                BlueprintCodec::encode_string(&expected).unwrap()
+            );
+            fb_container_free(container);
+        }
+    }
+}
+
+#[test]
+fn json_roundtrips() {
+    let blueprint = std::fs::read_to_string(examples().next().unwrap()).unwrap();
+    let container = decode(&blueprint).unwrap();
+    unsafe {
+        let mut json = ptr::null_mut();
+        assert_eq!(fb_container_to_json(container, &mut json), FbStatus::Ok);
+        let json = take_string(json);
+        let mut read = ptr::null_mut();
+        assert_eq!(
+            fb_container_from_json(json.as_ptr().cast(), json.len(), &mut read),
+            FbStatus::Ok
+        );
+        assert_eq!(*read, *container);
+        fb_container_free(read);
+        fb_container_free(container);
+    }
+}
+
+#[test]
+fn errors_have_codes_and_messages() {
+    assert_eq!(decode("1abc").unwrap_err(), FbStatus::Version);
+    assert!(last_error().contains("version byte"));
+    assert_eq!(decode("0!!!!").unwrap_err(), FbStatus::Base64);
+    assert_eq!(decode("0AAAA").unwrap_err(), FbStatus::Zlib);
+    assert_eq!(decode("").unwrap_err(), FbStatus::Input);
+
+    let json = r#"{"blueprint": {"item": "blueprint", "version": 0, "entities": [{}]}}"#;
+    let mut container = ptr::null_mut();
+    let status =
+        unsafe { fb_container_from_json(json.as_ptr().cast(), json.len(), &mut container) };
+    assert_eq!(status, FbStatus::Json);
+    assert!(
+        container.is_null(),
+        "out parameters are untouched on failure"
+    );
+    assert!(last_error().contains("blueprint.entities[0]"));
+
+    let status = unsafe { fb_decode(ptr::null(), 0, &mut container) };
+    assert_eq!(status, FbStatus::NullPointer);
+    unsafe {
+        fb_container_free(ptr::null_mut());
+        fb_string_free(ptr::null_mut());
+    }
+}
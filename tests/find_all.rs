@@ -0,0 +1,52 @@
+use factorio_blueprint::BlueprintCodec;
+
+mod common;
+use common::examples;
+
+fn wrap(string: &str, width: usize) -> String {
+    string
+        .as_bytes()
+        .chunks(width)
+        .map(|line| std::str::from_utf8(line).unwrap())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn finds_strings_in_prose() {
+    let strings: Vec<String> = examples()
+        .map(|example| std::fs::read_to_string(example).unwrap().trim().to_owned())
+        .collect();
+
+    let mut text = String::from("Here are my builds!\n\n");
+    for (idx, string) in strings.iter().enumerate() {
+        if idx % 2 == 0 {
+            text.push_str(&format!("Build {}: {} (enjoy)\n", idx, string));
+        } else {
+            text.push_str(&format!("Build {}:\n{}\nThanks\n", idx, wrap(string, 80)));
+        }
+    }
+
+    let found: Vec<_> = BlueprintCodec::find_all(&text).collect();
+    assert_eq!(found.len(), strings.len());
+    for ((range, result), string) in found.into_iter().zip(&strings) {
+        assert_eq!(text[range].split_whitespace().collect::<String>(), *string);
+        assert_eq!(
+            result.unwrap(),
+            BlueprintCodec::decode_string(string).unwrap()
+        );
+    }
+}
+
+#[test]
+fn reports_decoys() {
+    let found: Vec<_> = BlueprintCodec::find_all("not a blueprint: 0eNxyz.").collect();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].0, 17..23);
+    assert!(found[0].1.is_err());
+}
+
+#[test]
+fn ignores_text_without_strings() {
+    assert_eq!(BlueprintCodec::find_all("x0eN 0e N").count(), 0);
+}
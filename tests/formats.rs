@@ -0,0 +1,47 @@
+use factorio_blueprint::{BlueprintCodec, Container, Error, Format};
+
+mod common;
+use common::examples;
+
+#[test]
+fn examples_roundtrip_through_available_formats() {
+    let formats = Format::ALL
+        .iter()
+        .copied()
+        .filter(|format| format.is_available());
+    let formats = formats.collect::<Vec<_>>();
+    for example in examples() {
+        let container =
+            BlueprintCodec::decode_string(&std::fs::read_to_string(&example).unwrap()).unwrap();
+        for &format in &formats {
+            let data = container.to_format(format).unwrap();
+            assert_eq!(
+                Container::from_format(&data, format).unwrap(),
+                container,
+                "{} as {}",
+                example.display(),
+                format
+            );
+        }
+    }
+}
+
+#[test]
+fn format_names_parse() {
+    for &format in &Format::ALL {
+        assert_eq!(format.name().parse::<Format>(), Ok(format));
+    }
+    assert_eq!("YML".parse::<Format>(), Ok(Format::Yaml));
+    assert!("xml".parse::<Format>().is_err());
+}
+
+#[test]
+fn unavailable_formats_are_reported() {
+    for &format in Format::ALL.iter().filter(|format| !format.is_available()) {
+        let container = Container::Blueprint(Default::default());
+        assert!(matches!(
+            container.to_format(format),
+            Err(Error::FormatUnavailable(found)) if found == format
+        ));
+    }
+}
@@ -0,0 +1,82 @@
+use factorio_blueprint::{
+    header::{ContainerHeader, PageHeader},
+    objects::BlueprintBookBlueprintValue,
+    BlueprintCodec, Container,
+};
+
+mod common;
+use common::examples;
+
+/// assert that a header agrees with the fully decoded container
+fn assert_matches(header: &ContainerHeader, container: &Container) {
+    match (header, container) {
+        (ContainerHeader::BlueprintBook(header), Container::BlueprintBook(book)) => {
+            assert_eq!(header.label, book.label);
+            assert_eq!(header.active_index, book.active_index);
+            assert_eq!(header.version, book.version);
+            assert_eq!(header.blueprints.len(), book.blueprints.len());
+            for (
+                PageHeader { index, item },
+                BlueprintBookBlueprintValue {
+                    index: book_index,
+                    item: book_item,
+                },
+            ) in header.blueprints.iter().zip(&book.blueprints)
+            {
+                assert_eq!(index, book_index);
+                assert_matches(item, book_item);
+            }
+        }
+        (ContainerHeader::Blueprint(header), Container::Blueprint(blueprint)) => {
+            assert_eq!(header.label, blueprint.label);
+            assert_eq!(header.icons, blueprint.icons);
+            assert_eq!(header.version, blueprint.version);
+            assert_eq!(header.entity_count, blueprint.entities.len());
+            assert_eq!(header.tile_count, blueprint.tiles.len());
+        }
+        (
+            ContainerHeader::DeconstructionPlanner(header),
+            Container::DeconstructionPlanner(planner),
+        ) => {
+            assert_eq!(header.label, planner.label);
+            assert_eq!(header.version, planner.version);
+        }
+        (ContainerHeader::UpgradePlanner(header), Container::UpgradePlanner(planner)) => {
+            assert_eq!(header.label, planner.label);
+            assert_eq!(header.version, planner.version);
+        }
+        _ => panic!("header {:?} does not match container", header),
+    }
+}
+
+#[test]
+fn headers_match_full_decode() {
+    for example in examples() {
+        let string = std::fs::read_to_string(&example).unwrap();
+        let header = BlueprintCodec::decode_header(string.as_bytes()).unwrap();
+        let container = BlueprintCodec::decode_string(&string).unwrap();
+        assert_matches(&header, &container);
+    }
+}
+
+#[test]
+fn page_index_may_follow_item() {
+    // the index follows the item, which the typed objects never produce
+    let json = r#"{"blueprint_book": {"item": "blueprint-book", "active_index": 0, "version": 0,
+        "blueprints": [{"blueprint": {"item": "blueprint", "entities": [{}, {}]}, "index": 3}]}}"#;
+    let mut string = Vec::new();
+    BlueprintCodec::encode_writer(&mut string, |mut writer| {
+        std::io::Write::write_all(&mut writer, json.as_bytes())
+    })
+    .unwrap();
+    match BlueprintCodec::decode_header(string.as_slice()).unwrap() {
+        ContainerHeader::BlueprintBook(book) => {
+            assert_eq!(book.blueprints[0].index, 3);
+            match &book.blueprints[0].item {
+                ContainerHeader::Blueprint(blueprint) => assert_eq!(blueprint.entity_count, 2),
+                other => panic!("unexpected page {:?}", other),
+            }
+        }
+        other => panic!("unexpected header {:?}", other),
+    }
+}
@@ -0,0 +1,57 @@
+use factorio_blueprint::{lenient::Repair, BlueprintCodec};
+
+mod common;
+use common::examples;
+
+/// mangle a blueprint string in all the ways that forums and chat clients do
+fn mangle(blueprint: &str) -> String {
+    let url_safe: String = blueprint
+        .trim()
+        .trim_end_matches('=')
+        .chars()
+        .map(|ch| match ch {
+            '+' => '-',
+            '/' => '_',
+            ch => ch,
+        })
+        .collect();
+    let mut wrapped = String::new();
+    for (idx, ch) in url_safe.chars().enumerate() {
+        if idx > 0 && idx % 80 == 0 {
+            wrapped.push('\u{200b}');
+        }
+        wrapped.push(ch);
+    }
+    format!("```\n\"{}\"\n```\n", wrapped)
+}
+
+#[test]
+fn strict_decoding_rejects_mangled_strings() {
+    let blueprint = std::fs::read_to_string(examples().next().unwrap()).unwrap();
+    assert!(BlueprintCodec::decode_string(&mangle(&blueprint)).is_err());
+}
+
+#[test]
+fn lenient_decoding_repairs_mangled_examples() {
+    for example in examples() {
+        let blueprint = std::fs::read_to_string(example).unwrap();
+        let expect = BlueprintCodec::decode_string(&blueprint).unwrap();
+
+        let (container, repairs) = BlueprintCodec::decode_lenient(&mangle(&blueprint)).unwrap();
+        assert_eq!(container, expect);
+        assert!(repairs.contains(&Repair::CodeFence));
+        assert!(repairs.contains(&Repair::Quotes));
+        assert!(repairs
+            .iter()
+            .any(|repair| matches!(repair, Repair::StrayCharacters { .. })));
+    }
+}
+
+#[test]
+fn lenient_decoding_of_clean_strings_reports_nothing() {
+    for example in examples() {
+        let blueprint = std::fs::read_to_string(example).unwrap();
+        let (_, repairs) = BlueprintCodec::decode_lenient(&blueprint).unwrap();
+        assert!(repairs.is_empty(), "{:?}", repairs);
+    }
+}
@@ -0,0 +1,94 @@
+use factorio_blueprint::{limits::DecodeLimits, BlueprintCodec, Error};
+use std::io::Write;
+
+mod common;
+use common::examples;
+
+fn example(name: &str) -> String {
+    let path = examples()
+        .find(|path| path.file_name().unwrap() == name)
+        .expect("example exists");
+    std::fs::read_to_string(path).unwrap()
+}
+
+#[test]
+fn examples_decode_within_default_limits() {
+    for example in examples() {
+        let blueprint = std::fs::read_to_string(example).unwrap();
+        BlueprintCodec::decode_with_limits(blueprint.as_bytes(), &DecodeLimits::default()).unwrap();
+    }
+}
+
+#[test]
+fn decompression_bomb() {
+    // a few kilobytes of blueprint string which expands to 64 MiB
+    let mut bomb = Vec::new();
+    BlueprintCodec::encode_writer(&mut bomb, |mut writer| {
+        writer.write_all(br#"{"blueprint":{"item":"blueprint","version":0,"label":""#)?;
+        let spaces = vec![b' '; 1024 * 1024];
+        for _ in 0..64 {
+            writer.write_all(&spaces)?;
+        }
+        writer.write_all(br#""}}"#)
+    })
+    .unwrap();
+    assert!(bomb.len() < 256 * 1024);
+
+    let limits = DecodeLimits {
+        max_decompressed_bytes: 1024 * 1024,
+        ..DecodeLimits::default()
+    };
+    let err = BlueprintCodec::decode_with_limits(bomb.as_slice(), &limits).unwrap_err();
+    assert!(
+        matches!(err, Error::DecompressedTooLarge { limit } if limit == 1024 * 1024),
+        "{:?}",
+        err
+    );
+}
+
+#[test]
+fn input_too_large() {
+    let blueprint = example("all_the_belts.txt");
+    let limits = DecodeLimits {
+        max_input_bytes: 100,
+        ..DecodeLimits::default()
+    };
+    let err = BlueprintCodec::decode_with_limits(blueprint.as_bytes(), &limits).unwrap_err();
+    assert!(
+        matches!(err, Error::InputTooLarge { limit: 100 }),
+        "{:?}",
+        err
+    );
+}
+
+#[test]
+fn books_too_deep() {
+    // minimal_nested contains a book within a book
+    let blueprint = example("minimal_nested.txt");
+    let limits = |max_book_depth| DecodeLimits {
+        max_book_depth,
+        ..DecodeLimits::default()
+    };
+    BlueprintCodec::decode_with_limits(blueprint.as_bytes(), &limits(2)).unwrap();
+    let err = BlueprintCodec::decode_with_limits(blueprint.as_bytes(), &limits(1)).unwrap_err();
+    assert!(matches!(err, Error::BooksTooDeep { limit: 1 }), "{:?}", err);
+}
+
+#[test]
+fn too_many_entities() {
+    let blueprint = example("all_the_belts.txt");
+    let container = BlueprintCodec::decode_string(&blueprint).unwrap();
+    let entities = match &container {
+        factorio_blueprint::Container::Blueprint(blueprint) => blueprint.entities.len(),
+        _ => panic!("expected a blueprint"),
+    };
+
+    let limits = |max_entities| DecodeLimits {
+        max_entities,
+        ..DecodeLimits::default()
+    };
+    BlueprintCodec::decode_with_limits(blueprint.as_bytes(), &limits(entities)).unwrap();
+    let err = BlueprintCodec::decode_with_limits(blueprint.as_bytes(), &limits(entities - 1))
+        .unwrap_err();
+    assert!(matches!(err, Error::TooManyEntities { .. }), "{:?}", err);
+}
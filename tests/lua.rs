@@ -0,0 +1,104 @@
+use factorio_blueprint::{
+    lua,
+    objects::{Blueprint, Entity},
+    BlueprintCodec, Container, Error,
+};
+
+mod common;
+use common::examples;
+
+#[test]
+fn examples_roundtrip_through_lua() {
+    for example in examples() {
+        let container =
+            BlueprintCodec::decode_string(&std::fs::read_to_string(&example).unwrap()).unwrap();
+        for lua in &[
+            lua::to_string(&container).unwrap(),
+            lua::to_string_pretty(&container).unwrap(),
+        ] {
+            assert_eq!(lua::from_str::<Container>(lua).unwrap(), container);
+        }
+    }
+}
+
+#[test]
+fn entities_map_like_blueprint_entities() {
+    let entities: Vec<Entity> = serde_json::from_value(serde_json::json!([
+        {
+            "entity_number": 1,
+            "name": "small-lamp",
+            "position": {"x": 0.5, "y": -1.5},
+            "connections": {"1": {"red": [{"entity_id": 2}]}},
+        },
+        {"entity_number": 2, "name": "constant-combinator", "position": {"x": 1.5, "y": -1.5}, "direction": 4},
+    ]))
+    .unwrap();
+    assert_eq!(
+        lua::to_string(&entities).unwrap(),
+        concat!(
+            r#"{{entity_number = 1, name = "small-lamp", position = {x = 0.5, y = -1.5}, "#,
+            r#"connections = {["1"] = {red = {{entity_id = 2}}}}}, "#,
+            r#"{entity_number = 2, name = "constant-combinator", position = {x = 1.5, y = -1.5}, "#,
+            r#"direction = 4}}"#,
+        )
+    );
+}
+
+#[test]
+fn serpent_output_is_read() {
+    let lua = r#"
+        -- exported with serpent.block
+        return {
+          blueprint = {
+            item = 'blueprint',
+            label = "Lamp\tone \"lit\"",
+            description = [[
+two
+lines]],
+            entities = { --[[table: 0x55d1c6f1e2a0]]
+              [1] = {
+                entity_number = 1;
+                name = "small-lamp",
+                position = {x = -0.5, y = 1.0},
+                connections = {["1"] = {green = {{entity_id = 1, circuit_id = 1}}}},
+              },
+            },
+            version = 281479275151360,
+          },
+        }
+    "#;
+    let blueprint = match lua::from_str::<Container>(lua).unwrap() {
+        Container::Blueprint(blueprint) => blueprint,
+        other => panic!("unexpected container {:?}", other),
+    };
+    assert_eq!(blueprint.label.as_deref(), Some("Lamp\tone \"lit\""));
+    assert_eq!(blueprint.description.as_deref(), Some("two\nlines"));
+    assert_eq!(blueprint.version, 281479275151360);
+    let entity = &blueprint.entities[0];
+    assert_eq!(entity.name, "small-lamp");
+    assert_eq!(
+        serde_json::to_value(entity.connections.as_ref().unwrap()).unwrap(),
+        serde_json::json!({"1": {"green": [{"entity_id": 1, "circuit_id": 1}]}})
+    );
+}
+
+#[test]
+fn errors_name_their_location() {
+    match lua::from_str::<Blueprint>("{\n  item = 'blueprint',\n  label = }") {
+        Err(Error::LuaSyntax { line, .. }) => assert_eq!(line, 3),
+        other => panic!("unexpected result {:?}", other),
+    }
+    match lua::from_str::<Blueprint>("{entities = {{entity_number = 1, name = true}}}") {
+        Err(Error::LuaValue { path, .. }) => assert_eq!(path, "entities[0].name"),
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn deep_nesting_is_rejected() {
+    let lua = "{".repeat(500_000);
+    match lua::from_str::<Container>(&lua) {
+        Err(Error::LuaSyntax { message, .. }) => assert!(message.contains("nested")),
+        other => panic!("unexpected result {:?}", other),
+    }
+}
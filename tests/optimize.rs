@@ -0,0 +1,73 @@
+use factorio_blueprint::{
+    objects::{Blueprint, ItemRequest},
+    optimize::minimize,
+    BlueprintCodec, Container,
+};
+
+mod common;
+use common::examples;
+
+fn canonical(mut container: Container) -> Container {
+    container.canonicalize();
+    container
+}
+
+#[test]
+fn minimal_strings_are_shorter_and_equivalent() {
+    for example in examples() {
+        let string = std::fs::read_to_string(&example).unwrap();
+        let container = BlueprintCodec::decode_string(&string).unwrap();
+        let optimized = BlueprintCodec::encode_minimal(&container).unwrap();
+        assert_eq!(
+            optimized.original_len,
+            BlueprintCodec::encode_string(&container).unwrap().len()
+        );
+        assert!(optimized.string.len() <= optimized.original_len);
+        assert_eq!(
+            optimized.bytes_saved(),
+            optimized.original_len - optimized.string.len()
+        );
+
+        let mut minimal = container;
+        minimize(&mut minimal);
+        assert_eq!(
+            canonical(BlueprintCodec::decode_string(&optimized.string).unwrap()),
+            canonical(minimal),
+            "{}",
+            example.display()
+        );
+    }
+}
+
+#[test]
+fn defaults_are_dropped() {
+    let mut container = Container::Blueprint(
+        serde_json::from_value(serde_json::json!({
+            "item": "blueprint",
+            "label": "",
+            "entities": [{
+                "entity_number": 1,
+                "name": "assembling-machine-2",
+                "position": {"x": 0.5, "y": 0.5},
+                "direction": 0,
+                "items": [{"item": "speed-module", "count": 2}],
+                "connections": {"1": {"red": [{"entity_id": 2, "circuit_id": 1}], "green": []}},
+            }],
+            "version": 281479275151360u64,
+        }))
+        .unwrap(),
+    );
+    minimize(&mut container);
+    let blueprint: Blueprint = match container {
+        Container::Blueprint(blueprint) => blueprint,
+        other => panic!("unexpected container {:?}", other),
+    };
+    assert_eq!(blueprint.label, None);
+    let entity = &blueprint.entities[0];
+    assert_eq!(entity.direction, None);
+    assert!(matches!(entity.items, Some(ItemRequest::Compact(_))));
+    assert_eq!(
+        serde_json::to_value(&entity.connections).unwrap(),
+        serde_json::json!({"1": {"red": [{"entity_id": 2}]}})
+    );
+}
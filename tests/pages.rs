@@ -0,0 +1,94 @@
+use factorio_blueprint::{
+    pages::{BookPage, Pages},
+    BlueprintCodec, Container, Error,
+};
+
+mod common;
+use common::examples;
+
+/// flatten a decoded book the way `Pages` does
+fn flatten(container: Container, path: &mut Vec<usize>, out: &mut Vec<BookPage>) {
+    if let Container::BlueprintBook(book) = container {
+        for (position, page) in book.blueprints.into_iter().enumerate() {
+            match page.item {
+                Container::BlueprintBook(_) => {
+                    path.push(position);
+                    flatten(page.item, path, out);
+                    path.pop();
+                }
+                _ => out.push(BookPage {
+                    path: path.clone(),
+                    page,
+                }),
+            }
+        }
+    }
+}
+
+fn pages(json: &str) -> Vec<Result<BookPage, Error>> {
+    Pages::new(json.as_bytes()).collect()
+}
+
+#[test]
+fn pages_match_full_decode() {
+    for example in examples() {
+        let string = std::fs::read_to_string(&example).unwrap();
+        let container = BlueprintCodec::decode_string(&string).unwrap();
+        if !matches!(container, Container::BlueprintBook(_)) {
+            continue;
+        }
+        let mut expect = Vec::new();
+        flatten(container, &mut Vec::new(), &mut expect);
+
+        let mut streamed = Vec::new();
+        BlueprintCodec::decode_pages(string.as_bytes(), |pages| {
+            for page in pages {
+                streamed.push(page?);
+            }
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(streamed, expect, "{}", example.display());
+    }
+}
+
+#[test]
+fn nested_books_and_trailing_index() {
+    let json = r#"{"blueprint_book": {"label": "outer", "blueprints": [
+        {"blueprint": {"item": "blueprint", "version": 1}, "index": 4},
+        {"blueprint_book": {"blueprints": [
+            {"index": 0, "upgrade_planner": {"item": "upgrade-planner", "version": 1}}
+        ], "item": "blueprint-book", "version": 1, "active_index": 0}, "index": 7}
+    ], "item": "blueprint-book", "version": 1, "active_index": 0}}"#;
+    let pages = pages(json)
+        .into_iter()
+        .map(|page| {
+            let page = page.unwrap();
+            (page.path, page.page.index)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(pages, vec![(vec![], 4), (vec![1], 0)]);
+}
+
+#[test]
+fn errors_name_the_page() {
+    let json = r#"{"blueprint_book": {"blueprints": [
+        {"index": 0, "blueprint": {"item": "blueprint", "version": 1}},
+        {"index": 1, "blueprint": {"item": "blueprint", "version": "one"}}
+    ]}}"#;
+    let pages = pages(json);
+    assert_eq!(pages.len(), 2);
+    assert!(pages[0].is_ok());
+    match &pages[1] {
+        Err(Error::Json { path, .. }) => {
+            assert_eq!(path, "blueprint_book.blueprints[1].blueprint.version")
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn only_books_have_pages() {
+    let pages = pages(r#"{"blueprint": {"item": "blueprint", "version": 1}}"#);
+    assert!(matches!(pages.as_slice(), [Err(Error::Json { .. })]));
+}
@@ -0,0 +1,70 @@
+#![cfg(feature = "rayon")]
+
+use factorio_blueprint::{encode_options::EncodeOptions, BlueprintCodec, Error};
+
+mod common;
+use common::examples;
+
+#[test]
+fn parallel_decode_matches_sequential() {
+    for example in examples() {
+        let string = std::fs::read_to_string(&example).unwrap();
+        assert_eq!(
+            BlueprintCodec::decode_parallel(string.as_bytes()).unwrap(),
+            BlueprintCodec::decode_string(&string).unwrap(),
+        );
+    }
+}
+
+#[test]
+fn parallel_encode_matches_sequential() {
+    for example in examples() {
+        let container =
+            BlueprintCodec::decode_string(&std::fs::read_to_string(&example).unwrap()).unwrap();
+        for options in &[EncodeOptions::default(), EncodeOptions::canonical()] {
+            let mut sequential = Vec::new();
+            BlueprintCodec::encode_with(&mut sequential, &container, options).unwrap();
+            let mut parallel = Vec::new();
+            BlueprintCodec::encode_parallel_with(&mut parallel, &container, options).unwrap();
+            assert_eq!(parallel, sequential);
+        }
+    }
+}
+
+#[test]
+fn parallel_decode_reports_sequential_errors() {
+    let json = r#"{"blueprint_book": {"blueprints": [
+        {"index": 0, "blueprint": {"item": "blueprint", "version": "one"}}
+    ]}}"#;
+    let mut string = Vec::new();
+    BlueprintCodec::encode_writer(&mut string, |mut writer| {
+        std::io::Write::write_all(&mut writer, json.as_bytes())
+    })
+    .unwrap();
+    match BlueprintCodec::decode_parallel(string.as_slice()) {
+        Err(Error::Json { path, .. }) => {
+            assert_eq!(path, "blueprint_book.blueprints[0].blueprint.version")
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn parallel_encode_writes_every_book_field() {
+    let book = r#"{"item": "blueprint-book", "label": "inner",
+        "label_color": {"r": 1, "g": 0.5, "b": 0, "a": 1}, "description": "nested",
+        "icons": [{"index": 1, "signal": {"name": "wooden-chest", "type": "item"}}],
+        "active_index": 1, "version": 281479273447424,
+        "blueprints": [{"index": 1, "blueprint": {"item": "blueprint", "version": 0}}]}"#;
+    let json = format!(
+        r#"{{"blueprint_book": {{"item": "blueprint-book", "active_index": 0, "version": 0,
+            "blueprints": [{{"index": 0, "blueprint_book": {}}}]}}}}"#,
+        book
+    );
+    let container = serde_json::from_str(&json).unwrap();
+    let mut sequential = Vec::new();
+    BlueprintCodec::encode(&mut sequential, &container).unwrap();
+    let mut parallel = Vec::new();
+    BlueprintCodec::encode_parallel(&mut parallel, &container).unwrap();
+    assert_eq!(parallel, sequential);
+}
@@ -1,7 +1,9 @@
 use assert_json_diff::assert_json_eq;
 use factorio_blueprint::{BlueprintCodec, Container, Error, Result};
 use std::io::{BufReader, Read};
-use std::path::PathBuf;
+
+mod common;
+use common::examples;
 
 fn test_parse<R: Read>(reader: R) {
     let mut json_data = Vec::new();
@@ -64,22 +66,6 @@ fn decode_to_json_value<R: std::io::Read>(reader: R) -> Result<serde_json::value
     out
 }
 
-fn examples() -> impl Iterator<Item = PathBuf> {
-    let mut examples = std::env::current_exe().expect("can find test executable");
-    // target/debug/1/executable
-    for _ in 0..4 {
-        examples.pop();
-    }
-    examples.push("tests/examples");
-
-    std::fs::read_dir(examples)
-        .expect("should find examples dir")
-        .map(|maybe_example| maybe_example.expect("should find file").path())
-        .inspect(|example| {
-            dbg!(example);
-        })
-}
-
 #[test]
 fn roundtrip() {
     for example in examples() {
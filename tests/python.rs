@@ -0,0 +1,71 @@
+#![cfg(feature = "python")]
+
+use factorio_blueprint::{python::factorio_blueprint as module, BlueprintCodec};
+use pyo3::{ffi::c_str, prelude::*, types::PyDict, wrap_pymodule};
+
+mod common;
+use common::examples;
+
+/// run the python code with the module imported as `fb`
+fn run(code: &std::ffi::CStr, blueprint: &str) {
+    Python::initialize();
+    Python::attach(|py| {
+        let globals = PyDict::new(py);
+        globals.set_item("fb", wrap_pymodule!(module)(py)).unwrap();
+        globals.set_item("blueprint", blueprint).unwrap();
+        if let Err(err) = py.run(code, Some(&globals), None) {
+            err.print(py);
+            panic!("python raised {}", err);
+        }
+    });
+}
+
+#[test]
+fn examples_roundtrip_through_dicts() {
+    for example in examples() {
+        let blueprint = std::fs::read_to_string(&example).unwrap();
+        let container = BlueprintCodec::decode_string(&blueprint).unwrap();
+        run(
+            c_str!(
+                "
+container = fb.BlueprintCodec.decode(blueprint)
+assert isinstance(container, dict)
+assert fb.BlueprintCodec.decode(fb.BlueprintCodec.encode(container)) == container
+"
+            ),
+            &BlueprintCodec::encode_string(&container).unwrap(),
+        );
+    }
+}
+
+#[test]
+fn errors_raise_exceptions_by_stage() {
+    run(
+        c_str!(
+            "
+for string, error in [
+    ('', fb.InputError),
+    ('1abc', fb.VersionError),
+    ('0!!!!', fb.Base64Error),
+    ('0AAAA', fb.ZlibError),
+]:
+    try:
+        fb.BlueprintCodec.decode(string)
+    except error as err:
+        assert isinstance(err, fb.BlueprintError)
+    else:
+        raise AssertionError(string)
+
+try:
+    fb.BlueprintCodec.encode({'blueprint': {'item': 'blueprint', 'version': 0, 'entities': [
+        {'entity_number': 1, 'name': 7, 'position': {'x': 0, 'y': 0}},
+    ]}})
+except fb.JsonError as err:
+    assert 'blueprint.entities[0].name' in str(err), err
+else:
+    raise AssertionError('encoded an invalid container')
+"
+        ),
+        "",
+    );
+}
@@ -0,0 +1,113 @@
+#![cfg(feature = "qr")]
+
+use factorio_blueprint::{
+    qr::{self, QrOptions, MAX_CHUNK_LEN},
+    BlueprintCodec, Container, Error,
+};
+
+mod common;
+use common::examples;
+
+#[test]
+fn examples_roundtrip_through_payloads() {
+    let options = QrOptions::default();
+    for example in examples() {
+        let string = std::fs::read_to_string(&example).unwrap();
+        let mut payloads = qr::payloads(string.trim(), &options);
+        // scanning order and repeated scans should not matter
+        payloads.reverse();
+        payloads.push(payloads[0].clone());
+        assert_eq!(
+            qr::from_payloads(&payloads).unwrap(),
+            BlueprintCodec::decode_string(&string).unwrap()
+        );
+    }
+}
+
+#[test]
+fn examples_roundtrip_through_png() {
+    let options = QrOptions {
+        chunk_len: 2000,
+        module_size: 2,
+    };
+    for example in examples() {
+        let container =
+            BlueprintCodec::decode_string(&std::fs::read_to_string(&example).unwrap()).unwrap();
+        let mut images = qr::to_png(&container, &options).unwrap();
+        // scanning is slow, so only the smaller examples are printed
+        if images.len() > 8 {
+            continue;
+        }
+        images.reverse();
+        assert_eq!(qr::from_images(&images).unwrap(), container);
+    }
+}
+
+#[test]
+fn short_strings_need_one_plain_code() {
+    let string = std::fs::read_to_string(examples().next().unwrap()).unwrap();
+    let string = string.trim();
+    let payloads = qr::payloads(string, &QrOptions::default());
+    if string.len() <= QrOptions::default().chunk_len {
+        assert_eq!(payloads, vec![string.to_string()]);
+    }
+    let options = QrOptions {
+        chunk_len: 100,
+        ..QrOptions::default()
+    };
+    let payloads = qr::payloads(string, &options);
+    assert!(payloads[0].starts_with(&format!("1/{}:", payloads.len())));
+    assert_eq!(
+        qr::from_payloads(&payloads).unwrap(),
+        BlueprintCodec::decode_string(string).unwrap()
+    );
+}
+
+#[test]
+fn svg_codes_are_generated() {
+    let string = std::fs::read_to_string(examples().next().unwrap()).unwrap();
+    let container = BlueprintCodec::decode_string(&string).unwrap();
+    let options = QrOptions {
+        chunk_len: 100,
+        ..QrOptions::default()
+    };
+    let svgs = qr::to_svg(&container, &options).unwrap();
+    assert_eq!(svgs.len(), qr::payloads(string.trim(), &options).len());
+    assert!(svgs.iter().all(|svg| svg.contains("<svg")));
+}
+
+#[test]
+fn missing_parts_are_reported() {
+    let result: Result<Container, _> = qr::from_payloads(["1/3:0eNq", "3/3:abc"]);
+    assert!(matches!(
+        result,
+        Err(Error::QrMissing { part: 2, count: 3 })
+    ));
+}
+
+#[test]
+fn huge_sequences_are_rejected() {
+    let result: Result<Container, _> = qr::from_payloads(["1/999999999999:x"]);
+    assert!(matches!(result, Err(Error::Qr(_))), "{:?}", result);
+}
+
+#[test]
+fn payloads_of_the_largest_chunk_len_fit() {
+    let largest = examples()
+        .max_by_key(|example| std::fs::metadata(example).unwrap().len())
+        .unwrap();
+    let container =
+        BlueprintCodec::decode_string(&std::fs::read_to_string(largest).unwrap()).unwrap();
+    let string = BlueprintCodec::encode_string(&container).unwrap();
+    let options = QrOptions {
+        chunk_len: MAX_CHUNK_LEN,
+        ..QrOptions::default()
+    };
+    let payloads = qr::payloads(&string, &options);
+    assert!(payloads.len() > 1);
+    assert_eq!(payloads.iter().map(String::len).max(), Some(MAX_CHUNK_LEN));
+    assert_eq!(
+        qr::to_svg(&container, &options).unwrap().len(),
+        payloads.len()
+    );
+}
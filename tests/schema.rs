@@ -0,0 +1,154 @@
+#![cfg(feature = "schemars")]
+
+use factorio_blueprint::objects::schema;
+use flate2::read::ZlibDecoder;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::Read;
+
+mod common;
+use common::examples;
+
+fn validator() -> jsonschema::Validator {
+    let schema = serde_json::to_value(schema()).unwrap();
+    jsonschema::validator_for(&schema).unwrap()
+}
+
+/// the json in a blueprint string, exactly as the game wrote it
+fn raw_json(blueprint: &str) -> Value {
+    let compressed = base64::decode(&blueprint.trim()[1..]).unwrap();
+    let mut json = String::new();
+    ZlibDecoder::new(compressed.as_slice())
+        .read_to_string(&mut json)
+        .unwrap();
+    serde_json::from_str(&json).unwrap()
+}
+
+#[test]
+fn examples_are_valid() {
+    let validator = validator();
+    for example in examples() {
+        let json = raw_json(&std::fs::read_to_string(&example).unwrap());
+        let errors: Vec<_> = validator
+            .iter_errors(&json)
+            .map(|err| format!("{} at {}", err, err.instance_path))
+            .collect();
+        assert!(errors.is_empty(), "{:?}: {:#?}", example, errors);
+    }
+}
+
+#[test]
+fn invalid_json_is_rejected() {
+    let validator = validator();
+    let blueprint = |entity: Value| {
+        json!({"blueprint": {
+            "item": "blueprint",
+            "label_color": {"r": 1, "g": 0, "b": 0, "a": 1},
+            "entities": [entity],
+            "version": 0,
+        }})
+    };
+    let belt = json!({
+        "entity_number": 1,
+        "name": "transport-belt",
+        "position": {"x": 0.5, "y": 0.5},
+        "direction": 2,
+        "connections": {"1": {"red": [{"entity_id": 2}]}},
+        "items": {"speed-module": 2},
+    });
+    assert!(validator.is_valid(&blueprint(belt.clone())));
+
+    let invalid = [
+        // directions are integers
+        ("direction", json!("east")),
+        ("direction", json!(8)),
+        // entity numbers start at 1
+        ("entity_number", json!(0)),
+        ("position", json!({"x": 0})),
+        ("connections", json!({"1": "red"})),
+        ("items", json!([{"item": "speed-module"}])),
+    ];
+    for (field, value) in invalid {
+        let mut entity = belt.clone();
+        entity[field] = value;
+        assert!(!validator.is_valid(&blueprint(entity)), "{}", field);
+    }
+
+    let mut colored = blueprint(belt);
+    colored["blueprint"]["label_color"] = json!("red");
+    assert!(!validator.is_valid(&colored));
+}
+
+/// the declarations in the TypeScript of the wasm package, by name, with
+/// the fields of each interface and whether they are optional
+fn typescript() -> HashMap<String, Option<Vec<(String, bool)>>> {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/src/wasm.d.ts");
+    let source = std::fs::read_to_string(path).unwrap();
+    let mut declarations = HashMap::new();
+    let mut lines = source.lines();
+    while let Some(line) = lines.next() {
+        if let Some(name) = line.strip_prefix("export type ") {
+            let name = name.split(' ').next().unwrap();
+            declarations.insert(name.to_string(), None);
+        } else if let Some(name) = line.strip_prefix("export interface ") {
+            let fields = lines
+                .by_ref()
+                .take_while(|line| *line != "}")
+                .filter_map(|line| line.strip_prefix("    ")?.split_once(':'))
+                .filter(|(field, _)| !field.starts_with(' ') && !field.starts_with("//"))
+                .map(|(field, _)| {
+                    let optional = field.ends_with('?');
+                    let field = field.trim_end_matches('?').trim_matches('"');
+                    (field.to_string(), optional)
+                })
+                .collect();
+            declarations.insert(name.trim_end_matches(" {").to_string(), Some(fields));
+        }
+    }
+    declarations
+}
+
+#[test]
+fn typescript_matches_the_schema() {
+    let schema = serde_json::to_value(schema()).unwrap();
+    let typescript = typescript();
+    for (name, definition) in schema["$defs"].as_object().unwrap() {
+        let properties = match definition["properties"].as_object() {
+            Some(properties) => properties,
+            None => continue,
+        };
+        let fields = match &typescript.get(name) {
+            Some(Some(fields)) => fields,
+            // a type alias, such as a union
+            Some(None) => continue,
+            None => panic!("no typescript declaration for {}", name),
+        };
+        let mut names = fields.iter().map(|(field, _)| field).collect::<Vec<_>>();
+        let mut expected = properties.keys().collect::<Vec<_>>();
+        names.sort();
+        expected.sort();
+        assert_eq!(names, expected, "fields of {}", name);
+
+        let required = definition["required"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        for (field, optional) in fields {
+            if *optional {
+                assert!(
+                    !required.contains(&json!(field)),
+                    "{}.{} is required",
+                    name,
+                    field
+                );
+            }
+        }
+    }
+    for name in typescript.keys() {
+        assert!(
+            name == "Container" || schema["$defs"].get(name).is_some(),
+            "{} is not in the schema",
+            name
+        );
+    }
+}
@@ -0,0 +1,151 @@
+use factorio_blueprint::{
+    encode_options::EncodeOptions,
+    objects::{BlueprintBook, BlueprintBookBlueprintValue},
+    split::split_book,
+    BlueprintCodec, Container, Error,
+};
+
+fn book(name: &str) -> BlueprintBook {
+    let path = format!("{}/tests/examples/{}.txt", env!("CARGO_MANIFEST_DIR"), name);
+    match BlueprintCodec::decode_string(&std::fs::read_to_string(path).unwrap()).unwrap() {
+        Container::BlueprintBook(book) => book,
+        other => panic!("unexpected container {:?}", other),
+    }
+}
+
+/// every page which is not a book, in order
+fn leaves(book: &BlueprintBook) -> Vec<Container> {
+    let mut pages = book.blueprints.clone();
+    pages.sort_by_key(|page| page.index);
+    pages
+        .into_iter()
+        .flat_map(|page| match page.item {
+            Container::BlueprintBook(nested) => leaves(&nested),
+            item => vec![item],
+        })
+        .collect()
+}
+
+fn encoded_len(book: &BlueprintBook) -> usize {
+    BlueprintCodec::encode_string(&Container::BlueprintBook(book.clone()))
+        .unwrap()
+        .len()
+}
+
+#[test]
+fn parts_fit_and_keep_every_page() {
+    let book = book("nilaus_base_in_a_book");
+    let parts = split_book(&book, 20_000, &EncodeOptions::default()).unwrap();
+    assert!(parts.len() > 1);
+    for (n, part) in parts.iter().enumerate() {
+        assert!(encoded_len(part) <= 20_000);
+        assert_eq!(
+            part.label.as_deref(),
+            Some(format!("Nilaus' Base-In-A-Book ({}/{})", n + 1, parts.len()).as_str())
+        );
+        assert_eq!(part.icons, book.icons);
+    }
+    assert_eq!(
+        parts.iter().flat_map(leaves).collect::<Vec<_>>(),
+        leaves(&book)
+    );
+}
+
+#[test]
+fn nested_books_are_split_in_place() {
+    let nested = book("nilaus_base_in_a_book");
+    let mut outer = book("science_book_modular");
+    let first = outer.blueprints.remove(0);
+    outer.blueprints = vec![
+        BlueprintBookBlueprintValue {
+            index: 0,
+            item: Container::BlueprintBook(nested.clone()),
+        },
+        BlueprintBookBlueprintValue { index: 1, ..first },
+    ];
+
+    let parts = split_book(&outer, 20_000, &EncodeOptions::default()).unwrap();
+    assert_eq!(
+        parts.iter().flat_map(leaves).collect::<Vec<_>>(),
+        leaves(&outer)
+    );
+    let first_part = &parts[0].blueprints[0].item;
+    match first_part {
+        Container::BlueprintBook(part) => {
+            assert!(part
+                .label
+                .as_deref()
+                .unwrap()
+                .starts_with("Nilaus' Base-In-A-Book (1/"))
+        }
+        other => panic!("unexpected page {:?}", other),
+    }
+}
+
+#[test]
+fn small_books_are_unchanged() {
+    let book = book("science_book_modular");
+    let parts = split_book(&book, 1_000_000, &EncodeOptions::default()).unwrap();
+    assert_eq!(parts, vec![book]);
+}
+
+#[test]
+fn oversized_pages_are_reported() {
+    let book = book("science_book_modular");
+    match split_book(&book, 500, &EncodeOptions::default()) {
+        Err(Error::PageTooLarge { path, len, limit }) => {
+            assert_eq!(path, vec![0]);
+            assert!(len > limit);
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn pages_are_packed_in_index_order() {
+    let mut book = book("nilaus_base_in_a_book");
+    book.blueprints.sort_by_key(|page| page.index);
+    let sorted = split_book(&book, 20_000, &EncodeOptions::default()).unwrap();
+
+    book.blueprints.reverse();
+    let parts = split_book(&book, 20_000, &EncodeOptions::default()).unwrap();
+    assert_eq!(
+        parts.iter().flat_map(leaves).collect::<Vec<_>>(),
+        leaves(&book)
+    );
+    assert_eq!(parts, sorted);
+}
+
+/// text which does not compress
+fn noise(len: usize) -> String {
+    let mut state = 1u32;
+    (0..len)
+        .map(|_| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (b'a' + (state >> 16) as u8 % 26) as char
+        })
+        .collect()
+}
+
+#[test]
+fn oversized_books_are_reported() {
+    let mut empty = book("empty_blueprint_book");
+    empty.description = Some(noise(20_000));
+    match split_book(&empty, 1_000, &EncodeOptions::default()) {
+        Err(Error::BookTooLarge { path, len, limit }) => {
+            assert_eq!(path, Vec::<usize>::new());
+            assert!(len > limit);
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+
+    let mut outer = book("science_book_modular");
+    outer.blueprints.push(BlueprintBookBlueprintValue {
+        index: outer.blueprints.len(),
+        item: Container::BlueprintBook(empty),
+    });
+    match split_book(&outer, 10_000, &EncodeOptions::default()) {
+        Err(Error::BookTooLarge { path, .. }) => assert_eq!(path, vec![outer.blueprints.len() - 1]),
+        other => panic!("unexpected result {:?}", other),
+    }
+}
@@ -1,20 +1,42 @@
 #!/usr/bin/env bash
 
+# Checks that the CLI and the Python bindings decode every example to the
+# same json.
+
 set -e
 
 cd "$(git rev-parse --show-toplevel)"
 
-if ! [ -x target/release/factorio-blueprint ]; then
-    cargo build --release
-fi
+cargo build --release --features codec-cli
+
+# python imports extension modules by their bare name
+module_dir="$(mktemp -d)"
+trap 'rm -rf "$module_dir"' EXIT
+cargo build --release --lib --features python
+for library in target/release/libfactorio_blueprint.{so,dylib}; do
+    if [ -f "$library" ]; then
+        cp "$library" "$module_dir/factorio_blueprint.so"
+    fi
+done
+
+decode_python () {
+    PYTHONPATH="$module_dir" python3 -c '
+import json, sys
+from factorio_blueprint import BlueprintCodec
+
+with open(sys.argv[1]) as f:
+    print(json.dumps(BlueprintCodec.decode(f.read())))
+' "$1"
+}
 
 check () {
     path="$1"
     filename="$(basename "$path")"
 
-    # pipe the output through jq so inconsequential style differences vanish
-    python_output="$(src/deflate.py "$path" | jq .)"
-    rust_output="$(target/release/factorio-blueprint decode --file "$path" | jq .)"
+    # pipe the output through jq so inconsequential style differences, and
+    # the order of keys, vanish
+    python_output="$(decode_python "$path" | jq -S .)"
+    rust_output="$(target/release/factorio-blueprint decode --file "$path" | jq -S .)"
 
     if [ "$python_output" != "$rust_output" ]; then
         exit_code=1
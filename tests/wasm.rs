@@ -0,0 +1,158 @@
+#![cfg(feature = "wasm")]
+//! Run with `cargo test --target wasm32-unknown-unknown --features wasm --test wasm`,
+//! which needs `wasm-bindgen-test-runner` from wasm-bindgen-cli; the tests
+//! run in node. Natively, this only checks that every example is listed.
+
+// there is no filesystem to read the examples from
+macro_rules! examples {
+    ($($name:literal),* $(,)?) => {
+        &[$(($name, include_str!(concat!("examples/", $name, ".txt")))),*]
+    };
+}
+
+const EXAMPLES: &[(&str, &str)] = examples![
+    "all_the_belts",
+    "all_the_combinators",
+    "all_the_inserters",
+    "belt_control_behavior_and_content_read_modes",
+    "big_ass_mall",
+    "circuit_entities_configured",
+    "circuit_entities_simple",
+    "easy_tile_solar_array",
+    "empty_blueprint_book",
+    "empty_blueprint_in_book",
+    "empty_deconstruction",
+    "empty_upgrade",
+    "halfway_setup_upgrade",
+    "inserter_circuit_network",
+    "inserter_circuit_network_none_operation",
+    "inserter_logistic_network",
+    "label_color_red",
+    "label_has_icon_and_emoji",
+    "labels_icons_descriptions_blueprint",
+    "labels_icons_descriptions_deconstruction",
+    "labels_icons_descriptions_upgrade",
+    "minimal_nested",
+    "modular_mall_krastorio",
+    "neighbours_defined",
+    "nilaus_base_in_a_book",
+    "nuclear_reactor_1.1gw",
+    "power_book",
+    "power_switch_copper_red_and_green_connected",
+    "pump_logistic_and_circuit_conditions",
+    "requester_chest_modes_of_operation",
+    "roboport_all_modes",
+    "roboport_no_total_logistic_output_signal",
+    "science_book_modular",
+    "setup_deconstruction",
+    "snap_to_grid_absolute",
+    "snap_to_grid_relative",
+    "super_compact_tileable_mining",
+    "tiered_universal_smelters",
+    "tileable_science_0.17_early_mid",
+    "train_schedule_all_wait_conditions",
+    "train_schedule_no_wait_conditions",
+    "train_simple",
+    "train_stop_modes_of_operation",
+];
+
+#[cfg(not(target_arch = "wasm32"))]
+mod common;
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn every_example_is_listed() {
+    let mut names = common::examples()
+        .map(|example| example.file_stem().unwrap().to_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    names.sort();
+    let listed = EXAMPLES.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+    assert_eq!(names, listed);
+}
+
+#[cfg(target_arch = "wasm32")]
+mod js {
+    use super::EXAMPLES;
+    use factorio_blueprint::{
+        wasm::{self, JsContainer},
+        BlueprintCodec, Container, Error,
+    };
+    use wasm_bindgen::{JsCast, JsValue};
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    fn message(err: wasm_bindgen::JsError) -> String {
+        JsValue::from(err)
+            .dyn_into::<js_sys::Error>()
+            .expect("errors are thrown as `Error`s")
+            .message()
+            .into()
+    }
+
+    #[wasm_bindgen_test]
+    fn decoded_objects_have_the_json_shape() {
+        for (name, blueprint) in EXAMPLES {
+            let container = BlueprintCodec::decode_string(blueprint).unwrap();
+            let object: JsValue = wasm::decode(blueprint).unwrap().into();
+            let json: String = js_sys::JSON::stringify(&object).unwrap().into();
+            assert_eq!(
+                serde_json::from_str::<serde_json::Value>(&json).unwrap(),
+                serde_json::to_value(&container).unwrap(),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn examples_roundtrip() {
+        for (name, blueprint) in EXAMPLES {
+            let container = BlueprintCodec::decode_string(blueprint).unwrap();
+            let encoded = wasm::encode(wasm::decode(blueprint).unwrap()).unwrap();
+            assert_eq!(
+                encoded,
+                BlueprintCodec::encode_string(&container).unwrap(),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn objects_built_in_javascript_can_be_encoded() {
+        let object = js_sys::JSON::parse(
+            r#"{"blueprint": {"item": "blueprint", "label": "js", "version": 281479273447424,
+                "entities": [{"entity_number": 1, "name": "wooden-chest",
+                              "position": {"x": 0.5, "y": 0.5}}]}}"#,
+        )
+        .unwrap();
+        let encoded = wasm::encode(object.unchecked_into::<JsContainer>()).unwrap();
+        let container = BlueprintCodec::decode_string(&encoded).unwrap();
+        match container {
+            Container::Blueprint(blueprint) => {
+                assert_eq!(blueprint.label.as_deref(), Some("js"));
+                assert_eq!(blueprint.entities.len(), 1);
+            }
+            _ => panic!("expected a blueprint"),
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn errors_are_thrown() {
+        let native = BlueprintCodec::decode_string("0!!!!").unwrap_err();
+        assert_eq!(
+            message(wasm::decode("0!!!!").unwrap_err()),
+            native.to_string()
+        );
+
+        let object = js_sys::JSON::parse(
+            r#"{"blueprint": {"item": "blueprint", "version": 0,
+                "entities": [{"entity_number": 1, "name": 7,
+                              "position": {"x": 0, "y": 0}}]}}"#,
+        )
+        .unwrap();
+        match wasm::from_js(object) {
+            Err(Error::JsValue { path, .. }) => assert_eq!(path, "blueprint.entities[0].name"),
+            other => panic!("expected a javascript value error, got {:?}", other),
+        }
+    }
+}